@@ -0,0 +1,221 @@
+use composer::{
+    ComposerLock, ComposerPackageAbandoned, ComposerVersion, ComposerVersionConstraint,
+};
+use fs_err as fs;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use warned::Warned;
+
+/// Path to a local copy of the `FriendsOfPHP/security-advisories` database
+pub(crate) const ADVISORIES_DIR_ENV_VAR: &str = "HEROKU_PHP_ADVISORIES_DIR";
+/// The lowest [`AdvisorySeverity`] of advisories that fail the build
+pub(crate) const ADVISORIES_FAIL_SEVERITY_ENV_VAR: &str = "HEROKU_PHP_ADVISORIES_FAIL_SEVERITY";
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum AdvisorySeverity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl FromStr for AdvisorySeverity {
+    type Err = AuditError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            "critical" => Ok(Self::Critical),
+            _ => Err(AuditError::InvalidFailSeverity(s.to_string())),
+        }
+    }
+}
+
+/// The [`AdvisorySeverity`] assumed for advisories that do not specify one, which is the case for
+/// all advisories in `FriendsOfPHP/security-advisories`.
+///
+/// These advisories therefore only fail the build if the fail severity is `low`.
+pub(crate) const UNKNOWN_ADVISORY_SEVERITY: AdvisorySeverity = AdvisorySeverity::Low;
+
+/// An advisory that affects the locked version of a package.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct Advisory {
+    pub(crate) package: String,
+    pub(crate) version: String,
+    pub(crate) title: String,
+    pub(crate) cve: Option<String>,
+    pub(crate) link: Option<String>,
+    /// Not part of the `FriendsOfPHP/security-advisories` format, but supported if present
+    pub(crate) severity: Option<AdvisorySeverity>,
+}
+
+impl fmt::Display for Advisory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.package, self.version, self.title)?;
+        if let Some(cve) = &self.cve {
+            write!(f, " [{cve}]")?;
+        }
+        if let Some(severity) = &self.severity {
+            write!(f, " [severity: {}]", format!("{severity:?}").to_lowercase())?;
+        }
+        if let Some(link) = &self.link {
+            write!(f, " <{link}>")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum AuditError {
+    /// The value of [`ADVISORIES_FAIL_SEVERITY_ENV_VAR`] is not a known severity
+    InvalidFailSeverity(String),
+    /// The directory given in [`ADVISORIES_DIR_ENV_VAR`] does not exist
+    AdvisoriesDirMissing(PathBuf),
+    ReadAdvisories(io::Error),
+    ParseAdvisory(PathBuf, serde_yaml_ng::Error),
+    /// The locked packages are affected by advisories with at least the configured fail severity
+    Vulnerable(Vec<Advisory>),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum AuditNotice {
+    /// The locked package with the given name is abandoned, optionally in favor of the given package
+    AbandonedPackage(String, Option<String>),
+    Advisory(Advisory),
+}
+
+/// An advisory file in the `FriendsOfPHP/security-advisories` format.
+#[derive(Deserialize)]
+struct AdvisoryFile {
+    title: String,
+    cve: Option<String>,
+    link: Option<String>,
+    /// The affected package, e.g. `composer://symfony/http-kernel`
+    reference: String,
+    branches: IndexMap<String, AdvisoryBranch>,
+    severity: Option<AdvisorySeverity>,
+}
+
+#[derive(Deserialize)]
+struct AdvisoryBranch {
+    /// Constraints that together describe the affected versions, e.g. `['>=5.0.0', '<5.4.20']`
+    versions: Vec<String>,
+}
+
+/// Audits the (non-dev) packages in the given lock file.
+///
+/// Abandoned packages are always reported. If an advisories directory is given, packages are also
+/// checked against its advisories; those with at least the given fail severity fail the audit, all
+/// others are reported. Advisories without a severity count as [`UNKNOWN_ADVISORY_SEVERITY`].
+pub(crate) fn audit(
+    lock: &ComposerLock,
+    advisories_dir: Option<&Path>,
+    fail_severity: Option<AdvisorySeverity>,
+) -> Result<Warned<(), AuditNotice>, AuditError> {
+    let mut notices = lock
+        .packages
+        .iter()
+        .filter_map(|package| match &package.package.abandoned {
+            Some(ComposerPackageAbandoned::Bool(true)) => {
+                Some(AuditNotice::AbandonedPackage(package.name.clone(), None))
+            }
+            Some(ComposerPackageAbandoned::Alternative(replacement)) => {
+                Some(AuditNotice::AbandonedPackage(
+                    package.name.clone(),
+                    Some(replacement.clone()).filter(|replacement| !replacement.is_empty()),
+                ))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(advisories_dir) = advisories_dir {
+        let (failing, reported) = affecting_advisories(lock, advisories_dir)?
+            .into_iter()
+            .partition::<Vec<_>, _>(|advisory| {
+                fail_severity.is_some_and(|fail_severity| {
+                    advisory.severity.unwrap_or(UNKNOWN_ADVISORY_SEVERITY) >= fail_severity
+                })
+            });
+        if !failing.is_empty() {
+            return Err(AuditError::Vulnerable(failing));
+        }
+        notices.extend(reported.into_iter().map(AuditNotice::Advisory));
+    }
+
+    Ok(Warned::new((), notices))
+}
+
+/// Finds the advisories in the given directory that affect the locked version of a package.
+///
+/// The directory is expected to have the layout of `FriendsOfPHP/security-advisories`, with one
+/// YAML file per advisory in a directory per package, e.g. `symfony/http-kernel/CVE-2022-24894.yaml`.
+fn affecting_advisories(
+    lock: &ComposerLock,
+    advisories_dir: &Path,
+) -> Result<Vec<Advisory>, AuditError> {
+    // otherwise, a typo in the path would silently disable the audit
+    if !advisories_dir.is_dir() {
+        return Err(AuditError::AdvisoriesDirMissing(
+            advisories_dir.to_path_buf(),
+        ));
+    }
+
+    let mut advisories = Vec::new();
+    for package in &lock.packages {
+        let package_dir = advisories_dir.join(&package.name);
+        if !package_dir.is_dir() {
+            continue;
+        }
+        // versions that cannot be parsed, like some branch names, cannot be checked
+        let Ok(version) = ComposerVersion::from_str(&package.version) else {
+            continue;
+        };
+
+        let mut paths = fs::read_dir(&package_dir)
+            .and_then(|entries| {
+                entries
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(AuditError::ReadAdvisories)?;
+        paths.retain(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "yaml" || extension == "yml")
+        });
+        paths.sort();
+
+        for path in paths {
+            let file = serde_yaml_ng::from_slice::<AdvisoryFile>(
+                &fs::read(&path).map_err(AuditError::ReadAdvisories)?,
+            )
+            .map_err(|e| AuditError::ParseAdvisory(path.clone(), e))?;
+
+            if file.reference != format!("composer://{}", package.name) {
+                continue;
+            }
+            let affected = file.branches.values().any(|branch| {
+                ComposerVersionConstraint::from_str(&branch.versions.join(","))
+                    .is_ok_and(|constraint| constraint.matches(&version))
+            });
+            if affected {
+                advisories.push(Advisory {
+                    package: package.name.clone(),
+                    version: package.version.clone(),
+                    title: file.title,
+                    cve: file.cve,
+                    link: file.link,
+                    severity: file.severity,
+                });
+            }
+        }
+    }
+    Ok(advisories)
+}
@@ -0,0 +1,96 @@
+//! An exec.d program that exports the memory available to a container at launch.
+//!
+//! The boot scripts size the PHP-FPM pool from the available memory and the `memory_limit` of the
+//! actual PHP-FPM configuration, including any `-F` config. The memory is determined from the
+//! cgroup (v2, then v1) limit, or the host memory if there is none, and exported as
+//! [`AVAILABLE_MEMORY_ENV_VAR`] (in bytes).
+//!
+//! `WEB_CONCURRENCY` itself is left alone, as setting it would override that calculation.
+
+// this binary shares the buildpack's dependencies, but only uses a few of them
+#![allow(unused_crate_dependencies)]
+
+use fs_err as fs;
+use libcnb::data::exec_d_program_output_key;
+use libcnb::exec_d::write_exec_d_program_output;
+
+/// The environment variable holding the memory available to the container, in bytes.
+const AVAILABLE_MEMORY_ENV_VAR: &str = "HEROKU_PHP_AVAILABLE_MEMORY";
+/// The cgroup v2 memory limit file; contains `max` if there is no limit.
+const CGROUP_V2_MEMORY_MAX: &str = "/sys/fs/cgroup/memory.max";
+/// The cgroup v1 memory limit file; contains a very large number if there is no limit.
+const CGROUP_V1_MEMORY_LIMIT: &str = "/sys/fs/cgroup/memory/memory.limit_in_bytes";
+
+fn main() {
+    // an explicitly configured value always wins
+    if std::env::var_os(AVAILABLE_MEMORY_ENV_VAR).is_some_and(|value| !value.is_empty()) {
+        return;
+    }
+
+    let Some(available_memory) = available_memory() else {
+        eprintln!("Could not determine available memory; not setting {AVAILABLE_MEMORY_ENV_VAR}.");
+        return;
+    };
+
+    write_exec_d_program_output([(
+        exec_d_program_output_key!("HEROKU_PHP_AVAILABLE_MEMORY"),
+        available_memory.to_string(),
+    )]);
+}
+
+/// The memory available to the container in bytes: its cgroup (v2, then v1) limit, but at most the host's total memory.
+fn available_memory() -> Option<u64> {
+    let cgroup_limit = [CGROUP_V2_MEMORY_MAX, CGROUP_V1_MEMORY_LIMIT]
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| parse_cgroup_memory_limit(&contents));
+    let host_memory = fs::read_to_string("/proc/meminfo")
+        .ok()
+        .and_then(|contents| parse_meminfo_total(&contents));
+
+    match (cgroup_limit, host_memory) {
+        (Some(limit), Some(total)) => Some(limit.min(total)),
+        (limit, total) => limit.or(total),
+    }
+}
+
+/// Parses the contents of a cgroup memory limit file; `None` means there is no limit.
+fn parse_cgroup_memory_limit(contents: &str) -> Option<u64> {
+    contents.trim().parse().ok()
+}
+
+/// Parses the `MemTotal` entry (given in kB) from the contents of `/proc/meminfo` into bytes.
+fn parse_meminfo_total(contents: &str) -> Option<u64> {
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("MemTotal:"))
+        .and_then(|value| {
+            value
+                .trim()
+                .trim_end_matches("kB")
+                .trim()
+                .parse::<u64>()
+                .ok()
+        })
+        .map(|kilobytes| kilobytes * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cgroup_memory_limit() {
+        assert_eq!(parse_cgroup_memory_limit("536870912\n"), Some(536_870_912));
+        assert_eq!(parse_cgroup_memory_limit("max\n"), None);
+    }
+
+    #[test]
+    fn meminfo_total() {
+        assert_eq!(
+            parse_meminfo_total("MemTotal:        2048000 kB\nMemFree:          100000 kB\n"),
+            Some(2_097_152_000)
+        );
+        assert_eq!(parse_meminfo_total("MemFree:          100000 kB\n"), None);
+    }
+}
@@ -0,0 +1,81 @@
+use crate::platform::automatic::AutomaticRequirement;
+use crate::utils::is_truthy;
+use fs_err as fs;
+use indexmap::IndexMap;
+use libcnb::Env;
+use std::path::Path;
+
+/// The service binding type that provides Blackfire server credentials.
+pub(crate) const BINDING_TYPE: &str = "blackfire";
+
+/// The environment variable that, if set to a truthy value, disables the Blackfire probe and agent.
+pub(crate) const OPT_OUT_ENV_VAR: &str = "HEROKU_PHP_SKIP_BLACKFIRE";
+
+/// The platform package for the Blackfire probe (a PHP extension).
+pub(crate) const PROBE_PACKAGE: &str = "heroku-sys/ext-blackfire";
+/// The platform package for the Blackfire agent.
+pub(crate) const AGENT_PACKAGE: &str = "heroku-sys/blackfire";
+
+/// Checks whether Blackfire should be installed, which is the case if it [is configured](is_configured)
+/// and the [`OPT_OUT_ENV_VAR`] is not set to a truthy value.
+pub(crate) fn is_enabled(env: &Env, service_binding_root: Option<&Path>) -> bool {
+    !env.get_string_lossy(OPT_OUT_ENV_VAR).is_some_and(is_truthy)
+        && is_configured(env, service_binding_root)
+}
+
+/// Checks whether Blackfire server credentials are configured, either via the
+/// `BLACKFIRE_SERVER_ID` and `BLACKFIRE_SERVER_TOKEN` env vars, or via a service binding of type
+/// [`BINDING_TYPE`] in the given binding root directory.
+///
+/// The credentials themselves are never used during the build; the agent reads them on startup.
+pub(crate) fn is_configured(env: &Env, service_binding_root: Option<&Path>) -> bool {
+    let env_is_set = |name: &str| env.get_string_lossy(name).is_some_and(|v| !v.is_empty());
+
+    (env_is_set("BLACKFIRE_SERVER_ID") && env_is_set("BLACKFIRE_SERVER_TOKEN"))
+        || service_binding_root.is_some_and(has_binding)
+}
+
+fn has_binding(service_binding_root: &Path) -> bool {
+    let Ok(entries) = fs::read_dir(service_binding_root) else {
+        return false;
+    };
+    entries.filter_map(Result::ok).any(|entry| {
+        fs::read_to_string(entry.path().join("type"))
+            .is_ok_and(|binding_type| binding_type.trim() == BINDING_TYPE)
+    })
+}
+
+/// Checks whether the probe is to connect to an agent that runs elsewhere, which is the case if
+/// `BLACKFIRE_AGENT_SOCKET` is set during the build.
+///
+/// No agent is then installed or started.
+pub(crate) fn has_external_agent(env: &Env) -> bool {
+    env.get_string_lossy("BLACKFIRE_AGENT_SOCKET")
+        .is_some_and(|socket| !socket.is_empty())
+}
+
+/// The platform requirements for the Blackfire probe and, unless there is an
+/// [external agent](has_external_agent), the agent.
+///
+/// Any version will do, so that projects can still constrain e.g. `ext-blackfire` themselves.
+pub(crate) fn platform_require(external_agent: bool) -> Vec<AutomaticRequirement> {
+    [PROBE_PACKAGE]
+        .into_iter()
+        .chain((!external_agent).then_some(AGENT_PACKAGE))
+        .map(|name| AutomaticRequirement {
+            name: name.to_string(),
+            constraint: "*".to_string(),
+            reason: "Blackfire server credentials are configured".to_string(),
+            opt_out: Some(OPT_OUT_ENV_VAR.to_string()),
+        })
+        .collect()
+}
+
+/// The packages for the generated platform package to replace, which is the agent if there is an
+/// [external agent](has_external_agent), so that it does not get installed even if required.
+pub(crate) fn platform_replace(external_agent: bool) -> IndexMap<String, String> {
+    external_agent
+        .then(|| (AGENT_PACKAGE.to_string(), "*".to_string()))
+        .into_iter()
+        .collect()
+}
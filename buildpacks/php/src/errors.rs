@@ -1,19 +1,27 @@
 pub(crate) mod notices;
 
 use crate::PhpBuildpackError;
+use crate::audit::{
+    ADVISORIES_DIR_ENV_VAR, ADVISORIES_FAIL_SEVERITY_ENV_VAR, AuditError, UNKNOWN_ADVISORY_SEVERITY,
+};
+use crate::layers::blackfire::BlackfireLayerError;
 use crate::layers::bootstrap::BootstrapLayerError;
-use crate::layers::composer_env::ComposerEnvLayerError;
+use crate::layers::php_config::PhpConfigLayerError;
 use crate::layers::platform::PlatformLayerError;
+use crate::opcache::OpcachePreloadError;
 use crate::package_manager::composer::{
-    ComposerLockVersionError, DependencyInstallationError, PlatformExtractorError,
-    PlatformFinalizerError,
+    ComposerLockVersionError, ComposerSettingsError, DependencyInstallationError,
+    PlatformExtractorError, PlatformFinalizerError,
 };
-use crate::php_project::{PlatformJsonError, ProjectLoadError};
+use crate::php_project::{HerokuExtraError, PlatformJsonError, ProjectLoadError};
 use crate::platform::generator::{
     ComposerRepositoryFromRepositoryUrlError, PlatformGeneratorError,
 };
+use crate::platform::libraries::LibraryCheckError;
+use crate::platform::preflight::PreflightCheckError;
 use crate::platform::{PlatformRepositoryUrlError, WebserversJsonError};
 use crate::utils::DownloadUnpackError;
+use crate::web::WebProcessError;
 use bullet_stream::global::print;
 use const_format::formatcp;
 use indoc::{formatdoc, indoc};
@@ -82,6 +90,22 @@ impl PhpBuildpackError {
     pub(crate) fn on_error(self) {
         let (heading, message) = match self {
             PhpBuildpackError::ProjectLoad(e) => on_project_load_error(e),
+            PhpBuildpackError::HerokuExtra(e) => match e {
+                HerokuExtraError::Parse(filename, e) => (
+                    format!("Invalid 'extra.heroku' settings in '{filename}'"),
+                    formatdoc! {"
+                        {e}
+
+                        The 'extra.heroku' section in '{filename}' supports the following settings:
+                        - 'engines': version constraints for 'apache' and/or 'nginx'
+                        - 'apache-includes': a list with an Apache config file to include
+                        - 'nginx-includes': a list with an Nginx config file to include
+                        - 'fpm-config': a PHP-FPM config file to include
+                        - 'php-config': a list of PHP INI directives, like 'memory_limit=256M'
+                    "},
+                ),
+            },
+            PhpBuildpackError::Audit(e) => on_audit_error(e),
             PhpBuildpackError::BootstrapLayer(e) => on_bootstrap_layer_error(e),
             PhpBuildpackError::PlatformRepositoryUrl(e) => match e {
                 PlatformRepositoryUrlError::Split(e) => (
@@ -94,12 +118,74 @@ impl PhpBuildpackError {
                 ),
             },
             PhpBuildpackError::PlatformJson(e) => on_platform_json_error(e),
+            PhpBuildpackError::PreflightCheck(e) => on_preflight_check_error(e),
             PhpBuildpackError::WebserversJson(e) => match e {
                 WebserversJsonError::PlatformGenerator(e) => on_platform_generator_error(e),
             },
             PhpBuildpackError::PlatformLayer(e) => on_platform_layer_error(e),
+            PhpBuildpackError::LibraryCheck(e) => on_library_check_error(e),
             PhpBuildpackError::DependencyInstallation(e) => on_dependency_installation_error(e),
-            PhpBuildpackError::ComposerEnvLayer(e) => on_composer_env_layer_error(e),
+            PhpBuildpackError::OpcachePreload(e) => match e {
+                OpcachePreloadError::MissingScript(path) => (
+                    "OPcache preload script not found".to_string(),
+                    formatdoc! {"
+                        The file '{path}' given in 'extra.heroku.opcache-preload' in your
+                        'composer.json' does not exist after dependency installation.
+
+                        Please make sure that the path is relative to the root
+                        directory of your app, and that the file is either committed
+                        or generated during installation (e.g. by a Composer script).
+                    "},
+                ),
+            },
+            PhpBuildpackError::PhpConfigLayer(e) => match e {
+                PhpConfigLayerError::FindIniFiles(e) => (
+                    "Failed to find PHP INI files".to_string(),
+                    format_io_error(&e),
+                ),
+                PhpConfigLayerError::Write(e) => (
+                    "Failed to write PHP configuration".to_string(),
+                    formatdoc! {"
+                        {io_error}
+
+                        {INTERNAL_ERROR_HELP_STRING}
+                        ",
+                        io_error = format_io_error(&e),
+                    },
+                ),
+            },
+            PhpBuildpackError::WebProcess(e) => match e {
+                WebProcessError::MissingFile { key, path } => (
+                    format!("File for 'extra.heroku.{key}' not found"),
+                    formatdoc! {"
+                        The file '{path}' listed in 'extra.heroku.{key}' in your
+                        'composer.json' does not exist.
+
+                        Please make sure that the path is relative to the root
+                        directory of your app, and that the file is committed.
+                    "},
+                ),
+                WebProcessError::MultipleIncludes(key) => (
+                    format!("Too many files in 'extra.heroku.{key}'"),
+                    formatdoc! {"
+                        Only one file may be listed in 'extra.heroku.{key}' in your
+                        'composer.json'. To use several files, include them from
+                        that one file.
+                    "},
+                ),
+            },
+            PhpBuildpackError::BlackfireLayer(e) => match e {
+                BlackfireLayerError::WriteScript(e) => (
+                    "Failed to write Blackfire agent start script".to_string(),
+                    formatdoc! {"
+                        {io_error}
+
+                        {INTERNAL_ERROR_HELP_STRING}
+                        ",
+                        io_error = format_io_error(&e),
+                    },
+                ),
+            },
         };
         print::error(formatdoc! {"
             {heading}
@@ -109,6 +195,69 @@ impl PhpBuildpackError {
     }
 }
 
+fn on_audit_error(e: AuditError) -> (String, String) {
+    match e {
+        AuditError::InvalidFailSeverity(value) => (
+            format!("Invalid '{ADVISORIES_FAIL_SEVERITY_ENV_VAR}'"),
+            formatdoc! {"
+                The value '{value}' of environment variable '{ADVISORIES_FAIL_SEVERITY_ENV_VAR}'
+                is not a known severity. Valid values are 'low', 'medium', 'high' and 'critical'.
+            "},
+        ),
+        AuditError::AdvisoriesDirMissing(path) => (
+            "Security advisories not found".to_string(),
+            formatdoc! {"
+                The directory '{path}' given in environment variable '{ADVISORIES_DIR_ENV_VAR}'
+                does not exist. Please point it to a copy of the security advisories database.
+                ",
+                path = path.display(),
+            },
+        ),
+        AuditError::ReadAdvisories(e) => (
+            "Failed to read security advisories".to_string(),
+            formatdoc! {"
+                {io_error}
+
+                Please check that environment variable '{ADVISORIES_DIR_ENV_VAR}'
+                points to a readable copy of the security advisories database.
+                ",
+                io_error = format_io_error(&e),
+            },
+        ),
+        AuditError::ParseAdvisory(path, e) => (
+            "Failed to parse security advisory".to_string(),
+            formatdoc! {"
+                The file '{path}' from the security advisories database in
+                '{ADVISORIES_DIR_ENV_VAR}' could not be parsed:
+
+                {e}
+                ",
+                path = path.display(),
+            },
+        ),
+        AuditError::Vulnerable(advisories) => (
+            "Dependencies affected by security advisories".to_string(),
+            formatdoc! {"
+                The following locked dependencies are affected by security advisories
+                with a severity of at least that configured in '{ADVISORIES_FAIL_SEVERITY_ENV_VAR}'
+                (advisories without a severity count as '{unknown}'):
+
+                {advisories}
+
+                Please update the affected packages using 'composer update' on your local
+                computer, then commit the changed 'composer.lock' and deploy again.
+                ",
+                unknown = format!("{UNKNOWN_ADVISORY_SEVERITY:?}").to_lowercase(),
+                advisories = advisories
+                    .iter()
+                    .map(|advisory| format!("- {advisory}"))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            },
+        ),
+    }
+}
+
 fn on_project_load_error(e: ProjectLoadError) -> (String, String) {
     match e {
         ProjectLoadError::ComposerLockRead(filename, e)
@@ -126,6 +275,24 @@ fn on_project_load_error(e: ProjectLoadError) -> (String, String) {
                 message = format_serde_error(&e)
             },
         ),
+        ProjectLoadError::ComposerJsonInvalid(filename, errors) => (
+            format!("Invalid '{filename}'"),
+            formatdoc! {"
+                Your '{filename}' does not conform to the Composer schema.
+                The following problems were found (the locations are JSON pointers):
+
+                {problems}
+
+                Please run 'composer validate' on your local computer for verification.
+
+                If you believe this message to be in error, please report it.",
+                problems = errors
+                    .iter()
+                    .map(|error| format!("- {error}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            },
+        ),
         ProjectLoadError::ComposerLockParse(filename, e) => (
             format!("Failed to parse '{filename}'"),
             formatdoc! {"
@@ -146,7 +313,8 @@ fn on_project_load_error(e: ProjectLoadError) -> (String, String) {
             "No Composer lock file found".to_string(),
             formatdoc! {"
                 A '{lock_name}' file was not found in your project, but there
-                is a '{json_name}' file with dependencies inside 'require'.
+                is a '{json_name}' file with package dependencies inside
+                'require'.
 
                 The lock file is required in order to guarantee reliable and
                 reproducible installation of dependencies across platforms and
@@ -171,6 +339,16 @@ fn on_project_load_error(e: ProjectLoadError) -> (String, String) {
                 https://getcomposer.org/doc/01-basic-usage.md
             "},
         ),
+        ProjectLoadError::ComposerSettings(ComposerSettingsError::InvalidEnvVar(name, value)) => (
+            format!("Invalid '{name}'"),
+            formatdoc! {"
+                The value '{value}' of environment variable '{name}'
+                is not a valid number of seconds.
+
+                Please refer to the Composer documentation for details:
+                https://getcomposer.org/doc/03-cli.md#environment-variables
+            "},
+        ),
     }
 }
 
@@ -232,7 +410,7 @@ fn on_platform_layer_error(e: PlatformLayerError) -> (String, String) {
             "Failed to parse platform installer packages log".to_string(),
             INTERNAL_ERROR_HELP_STRING.to_string(),
         ),
-        PlatformLayerError::ComposerInstall(exit_status, output) => (
+        PlatformLayerError::ComposerInstall(exit_status, output, available_packages) => (
             "Failed to install platform dependencies".to_string(),
             match &exit_status.code() {
                 Some(2) => formatdoc! {"
@@ -257,7 +435,15 @@ fn on_platform_layer_error(e: PlatformLayerError) -> (String, String) {
 
                     For a list of supported runtimes & extensions on Heroku, please
                     refer to: https://devcenter.heroku.com/articles/php-support
-                "},
+                    {available}",
+                    available = available_packages.map_or(String::new(), |available| formatdoc! {"
+
+                        The following runtimes and extensions are available for your app's
+                        stack in the configured platform repositories:
+
+                        {available}
+                    "})
+                },
                 Some(exit_code) => formatdoc! {"
                     An error ({exit_code}) occurred during installation.
 
@@ -323,10 +509,154 @@ fn on_platform_json_error(e: PlatformJsonError) -> (String, String) {
                 "}
                 .to_string(),
             ),
+            PlatformFinalizerError::UnsupportedRuntimeVariantRequirement(requirements) => (
+                "Unsupported PHP runtime variant required".to_string(),
+                formatdoc! {"
+                    Your project, or one of its dependencies, requires a variant of
+                    the PHP runtime that is not available on Heroku:
+                    {requirements}
+
+                    All PHP runtimes on Heroku are built without thread safety
+                    (ZTS) and without debug symbols.
+
+                    Please perform the following steps locally on your computer to
+                    resolve this issue before attempting another deploy:
+                    1) remove the requirements listed above from 'composer.json',
+                       and/or update or remove the dependencies listed above
+                    2) run 'composer update' to re-generate the lock file
+                    3) stage changes using 'git add composer.json composer.lock'
+                    4) commit changes using 'git commit'
+
+                    For more information on selecting PHP runtimes, please refer to
+                    https://devcenter.heroku.com/articles/php-support
+                    ",
+                    requirements = requirements
+                        .iter()
+                        .map(|(requirer, variant)| match requirer {
+                            Some(name) => format!("- '{variant}' (required by '{name}')"),
+                            None => format!("- '{variant}' (required by your project)"),
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                },
+            ),
         },
     }
 }
 
+fn on_preflight_check_error(e: PreflightCheckError) -> (String, String) {
+    match e {
+        PreflightCheckError::UnsatisfiableRuntimeRequirement {
+            stack,
+            arch,
+            requirements,
+            available_versions,
+        } => (
+            "No matching PHP version available".to_string(),
+            formatdoc! {"
+                Your requirements for the PHP runtime cannot be satisfied by any
+                of the PHP versions available for your app's stack ({stack}, {arch}).
+
+                The following PHP version requirements were found:
+                {requirements}
+
+                The following PHP versions are available:
+                {available_versions}
+
+                Please perform the following steps locally on your computer to
+                resolve this issue before attempting another deploy:
+                1) adjust the requirement for 'php' in 'composer.json', and/or
+                   update or remove the dependencies listed above
+                2) run 'composer update' to re-generate the lock file
+                3) stage changes using 'git add composer.json composer.lock'
+                4) commit changes using 'git commit'
+
+                For a list of supported runtimes & extensions on Heroku, please
+                refer to: https://devcenter.heroku.com/articles/php-support
+                ",
+                requirements = requirements
+                    .iter()
+                    .map(|(requirer, constraint)| match requirer {
+                        Some(name) => format!("- '{constraint}' (required by '{name}')"),
+                        None => format!("- '{constraint}' (required by your project)"),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                available_versions = available_versions.join(", "),
+            },
+        ),
+    }
+}
+
+fn on_library_check_error(e: LibraryCheckError) -> (String, String) {
+    match e {
+        LibraryCheckError::PhpInvocation(e) => (
+            "Failed to determine library versions".to_string(),
+            formatdoc! {"
+                {io_error}
+
+                {INTERNAL_ERROR_HELP_STRING}
+                ",
+                io_error = format_io_error(&e),
+            },
+        ),
+        LibraryCheckError::PhpOutput(output) => (
+            "Failed to determine library versions".to_string(),
+            formatdoc! {"
+                A call to 'php' to determine the versions of installed libraries
+                failed with exit status {exit_status}, or produced invalid output.
+
+                This may indicate a problem with the installed PHP runtime or
+                extensions. Please check the following error output:
+
+                {stderr}
+
+                {INTERNAL_ERROR_HELP_STRING}
+                ",
+                exit_status = output.status.code().unwrap_or(-1),
+                stderr = String::from_utf8_lossy(&output.stderr),
+            },
+        ),
+        LibraryCheckError::UnsatisfiedRequirements { requirements } => (
+            "Library requirements not satisfied".to_string(),
+            formatdoc! {"
+                Your project, or one of its dependencies, requires versions of
+                libraries that do not match those available on your app's stack:
+                {requirements}
+
+                The versions of libraries such as ICU or OpenSSL that PHP and its
+                extensions use are determined by the stack, and cannot be changed.
+
+                Please perform the following steps locally on your computer to
+                resolve this issue before attempting another deploy:
+                1) adjust the requirements listed above in 'composer.json',
+                   and/or update or remove the dependencies listed above
+                2) run 'composer update' to re-generate the lock file
+                3) stage changes using 'git add composer.json composer.lock'
+                4) commit changes using 'git commit'
+
+                Alternatively, use a different stack for your app.
+                ",
+                requirements = requirements
+                    .iter()
+                    .map(|(requirement, installed)| {
+                        format!(
+                            "- '{constraint}' for '{name}' (required by {required_by}; installed version is '{installed}')",
+                            constraint = requirement.constraint,
+                            name = requirement.name,
+                            required_by = requirement
+                                .required_by
+                                .as_ref()
+                                .map_or("your project".to_string(), |name| format!("'{name}'")),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            },
+        ),
+    }
+}
+
 fn on_platform_generator_error(e: PlatformGeneratorError) -> (String, String) {
     match e {
         PlatformGeneratorError::EmptyPlatformRepositoriesList => (
@@ -388,30 +718,3 @@ fn on_dependency_installation_error(e: DependencyInstallationError) -> (String,
         ),
     }
 }
-
-fn on_composer_env_layer_error(e: ComposerEnvLayerError) -> (String, String) {
-    match e {
-        ComposerEnvLayerError::ComposerInvocation(e) => (
-            "An I/O error occurred".to_string(),
-            formatdoc! {"
-                Details: {e}
-
-                {INTERNAL_ERROR_HELP_STRING}
-            "},
-        ),
-        ComposerEnvLayerError::ComposerConfig(output) => (
-            "Could not determine Composer config value".to_string(),
-            formatdoc! {"
-                A call to 'composer config' failed with exit status {exit_status}.
-
-                This may indicate a mis-configuration in composer.json.
-                Please check the following error output from Composer to diagnose and address the problem:
-
-                {stderr}
-                ",
-                exit_status = output.status.code().unwrap_or(-1),
-                stderr = String::from_utf8_lossy(&output.stderr),
-            },
-        ),
-    }
-}
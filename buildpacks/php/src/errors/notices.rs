@@ -1,8 +1,16 @@
 use crate::PhpBuildpackNotice;
+use crate::audit::{ADVISORIES_FAIL_SEVERITY_ENV_VAR, AuditNotice};
+use crate::opcache::{CONVENTIONAL_PRELOAD_SCRIPTS, OpcachePreloadNotice};
 use crate::package_manager::composer::{
-    ComposerLockVersionNotice, PlatformExtractorNotice, PlatformFinalizerNotice,
+    ComposerLockVersionNotice, PHP_PLATFORM_OVERRIDE_ENV_VAR, PlatformExtractorNotice,
+    PlatformFinalizerNotice, PlatformOverrideNotice,
 };
 use crate::php_project::{PlatformJsonNotice, ProjectLoaderNotice};
+use crate::platform::generator::ComposerRepositoryFromRepositoryUrlError;
+use crate::platform::libraries::LibraryCheckNotice;
+use crate::platform::repository::PlatformRepositoryMetadataError;
+use crate::utils::DownloadUnpackError;
+use crate::web::WebServerNotice;
 use bullet_stream::global::print;
 use indoc::{formatdoc, indoc};
 
@@ -11,12 +19,41 @@ pub(crate) fn log(notice: PhpBuildpackNotice) {
 }
 
 #[rustfmt::skip] // formatting is much more consistent this way (line lengths!)
+#[allow(clippy::too_many_lines)]
 fn get_message(notice: PhpBuildpackNotice) -> String {
     match notice {
         PhpBuildpackNotice::ProjectLoader(n) => match n {
             ProjectLoaderNotice::NameFromEnvVar(name, value) => formatdoc! {"
                 Environment variable '{name}={value}' is overriding default `composer.json` file name.
             "},
+            ProjectLoaderNotice::ConfigFromEnvVar(name, value, setting) => formatdoc! {"
+                Environment variable '{name}={value}' is overriding Composer's '{setting}' setting.
+            "},
+            ProjectLoaderNotice::CacheDirFromEnvVarIgnored(value) => formatdoc! {"
+                Environment variable 'COMPOSER_CACHE_DIR={value}' is ignored; the buildpack manages Composer's cache directory.
+            "},
+            ProjectLoaderNotice::InvalidPackageNames(filename, problems) => formatdoc! {"
+                Your '{filename}' contains invalid package names (the locations are JSON pointers):
+                {problems}
+                Please run 'composer validate' on your local computer for details.
+            ", problems = problems.iter().map(|problem| format!("- {problem}")).collect::<Vec<_>>().join("\n")},
+            ProjectLoaderNotice::StaleLock(json_name, lock_name) => formatdoc! {"
+                Your '{lock_name}' is out of date with the latest changes in '{json_name}'.
+                Packages are installed from '{lock_name}', so these changes will not take effect.
+                Please run 'composer update' on your local computer and commit the updated '{lock_name}'.
+            "},
+        },
+        PhpBuildpackNotice::Audit(n) => match n {
+            AuditNotice::AbandonedPackage(name, None) => formatdoc! {"
+                Package '{name}' is abandoned and no longer maintained. Consider removing it or finding an alternative.
+            "},
+            AuditNotice::AbandonedPackage(name, Some(replacement)) => formatdoc! {"
+                Package '{name}' is abandoned and no longer maintained. Its authors suggest using '{replacement}' instead.
+            "},
+            AuditNotice::Advisory(advisory) => formatdoc! {"
+                Dependency affected by security advisory: {advisory}. \
+                Set '{ADVISORIES_FAIL_SEVERITY_ENV_VAR}' to fail builds on advisories of a given severity or higher.
+            "},
         },
         PhpBuildpackNotice::PlatformJson(n) => match n {
             PlatformJsonNotice::Extractor(n) => match n {
@@ -29,7 +66,21 @@ fn get_message(notice: PhpBuildpackNotice) -> String {
                         Lock file 'plugin-api-version' indicates contents were generated by a Composer release with plugin API version '{input}'. \
                         For best plugin compatibility, a Composer release matching plugin API version '{output}' will be used for installation.
                     "},
+                    ComposerLockVersionNotice::UnsatisfiedRuntimeApiRequirement(requirement, provided) => formatdoc! {"
+                        A dependency requires 'composer-runtime-api' version '{requirement}', but Composer releases known to this buildpack provide version '{provided}'. \
+                        The latest Composer 2 release will be used for installation; installing dependencies will fail unless it satisfies the requirement.
+                    "},
                 },
+                PlatformExtractorNotice::PhpPlatformOverrideApplied(version, series) => formatdoc! {"
+                    Restricting the PHP version requirement to series '{series}' of 'config.platform' version '{version}' of 'php', as '{PHP_PLATFORM_OVERRIDE_ENV_VAR}' is set.
+                "},
+            },
+            PlatformJsonNotice::AutomaticRequirement(r) => {
+                let name = r.name.strip_prefix("heroku-sys/").unwrap_or(&r.name);
+                let opt_out = r.opt_out.map(|env_var| format!(" To prevent this, set '{env_var}=1'.")).unwrap_or_default();
+                formatdoc! {"
+                    Automatically installing '{name}' (version '{constraint}') because {reason}.{opt_out}
+                ", constraint = r.constraint, reason = r.reason}
             },
             PlatformJsonNotice::Finalizer(n) => match n {
                 PlatformFinalizerNotice::RuntimeRequirementInserted(name, version) => formatdoc! {"
@@ -40,5 +91,47 @@ fn get_message(notice: PhpBuildpackNotice) -> String {
                 "}.to_string(),
             },
         },
+        PhpBuildpackNotice::LibraryCheck(n) => {
+            let (requirement, problem) = match n {
+                LibraryCheckNotice::UnknownLibraryVersion(r) => (r, "the installed version of the library could not be determined"),
+                LibraryCheckNotice::InvalidConstraint(r) => (r, "the version constraint is invalid"),
+            };
+            let required_by = requirement.required_by.map_or("your project".to_string(), |name| format!("'{name}'"));
+            formatdoc! {"
+                Requirement '{constraint}' for '{name}' (required by {required_by}) was not checked, as {problem}.
+            ", constraint = requirement.constraint, name = requirement.name}
+        },
+        PhpBuildpackNotice::OpcachePreload(n) => match n {
+            OpcachePreloadNotice::NoConventionalScript => formatdoc! {"
+                OPcache preloading is enabled in 'extra.heroku.opcache-preload', but none of the files '{scripts}' exist. \
+                To preload a different script, set 'extra.heroku.opcache-preload' to its path.
+            ", scripts = CONVENTIONAL_PRELOAD_SCRIPTS.join("', '")},
+        },
+        PhpBuildpackNotice::WebServer(n) => match n {
+            WebServerNotice::UnknownEngine(name) => formatdoc! {"
+                Ignoring unknown engine '{name}' in 'extra.heroku.engines'; only 'apache' and 'nginx' are supported.
+            "},
+        },
+        PhpBuildpackNotice::PlatformOverride(n) => match n {
+            PlatformOverrideNotice::InstalledVersionDiffers(name, version, installed) => {
+                let opt_in = if name == "php" { format!(" To install the '{version}' release series instead, set '{PHP_PLATFORM_OVERRIDE_ENV_VAR}=1'.") } else { String::new() };
+                formatdoc! {"
+                    Lock file was generated with 'config.platform' setting '{name}' to version '{version}', but version '{installed}' was installed. \
+                    This setting only affects dependency resolution; the version of '{name}' installed is determined by the requirements in the lock file.{opt_in}
+                "}
+            },
+        },
+        PhpBuildpackNotice::PlatformRepositoryMetadata(e) => {
+            let (url, details) = match e {
+                PlatformRepositoryMetadataError::FromRepositoryUrl(url, ComposerRepositoryFromRepositoryUrlError::MultipleFilters) => (url, "conflicting filters in URL".to_string()),
+                PlatformRepositoryMetadataError::Read(url, e)
+                | PlatformRepositoryMetadataError::Download(url, DownloadUnpackError::Io(e)) => (url, e.to_string()),
+                PlatformRepositoryMetadataError::Download(url, DownloadUnpackError::Request(e)) => (url, e.to_string()),
+                PlatformRepositoryMetadataError::Parse(url, e) => (url, e.to_string()),
+            };
+            formatdoc! {"
+                Failed to load platform repository '{url}' to determine available runtimes and extensions: {details}
+            "}
+        },
     }
 }
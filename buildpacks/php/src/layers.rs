@@ -1,4 +1,7 @@
+pub(crate) mod blackfire;
 pub(crate) mod bootstrap;
 pub(crate) mod composer_cache;
 pub(crate) mod composer_env;
+pub(crate) mod php_config;
 pub(crate) mod platform;
+pub(crate) mod web_concurrency;
@@ -0,0 +1,107 @@
+// TODO: Switch to libcnb's struct layer API.
+#![allow(deprecated)]
+
+use crate::{PhpBuildpack, PhpBuildpackError};
+use fs_err as fs;
+use indoc::formatdoc;
+use libcnb::Buildpack;
+use libcnb::build::BuildContext;
+use libcnb::data::layer_content_metadata::LayerTypes;
+use libcnb::generic::GenericMetadata;
+use libcnb::layer::{Layer, LayerResult, LayerResultBuilder};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+/// The default socket the agent listens on, and the probe connects to.
+const DEFAULT_AGENT_SOCKET: &str = "tcp://127.0.0.1:8307";
+
+/// Starts the Blackfire agent in the background when a container launches.
+///
+/// The server credentials are read at launch time, from the `BLACKFIRE_SERVER_ID` and
+/// `BLACKFIRE_SERVER_TOKEN` env vars or from a service binding, so they never end up in the image.
+pub(crate) struct BlackfireLayer;
+
+impl Layer for BlackfireLayer {
+    type Buildpack = PhpBuildpack;
+    type Metadata = GenericMetadata;
+
+    fn types(&self) -> LayerTypes {
+        LayerTypes {
+            build: false,
+            cache: false,
+            launch: true,
+        }
+    }
+
+    fn create(
+        &mut self,
+        _context: &BuildContext<Self::Buildpack>,
+        layer_path: &Path,
+    ) -> Result<LayerResult<Self::Metadata>, <Self::Buildpack as Buildpack>::Error> {
+        let script_path = layer_path.join("start-blackfire-agent");
+        fs::write(&script_path, agent_start_script())
+            .and_then(|()| {
+                fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755))
+            })
+            .map_err(BlackfireLayerError::WriteScript)?;
+
+        LayerResultBuilder::new(GenericMetadata::default())
+            .exec_d_program("start-blackfire-agent", script_path)
+            .build()
+    }
+}
+
+/// An exec.d program that launches the agent if credentials are available.
+///
+/// Since exec.d programs run for every process, the agent is only started if nothing is listening on
+/// its socket yet, e.g. because the process is a shell started inside a running container.
+///
+/// The agent is detached from file descriptor 3, which the launcher reads env var output from until
+/// it is closed, and `BLACKFIRE_AGENT_SOCKET` is then exported so the probe can find the agent.
+fn agent_start_script() -> String {
+    formatdoc! {r#"
+        #!/usr/bin/env bash
+
+        server_id=${{BLACKFIRE_SERVER_ID:-}}
+        server_token=${{BLACKFIRE_SERVER_TOKEN:-}}
+
+        if [[ -z "$server_id" || -z "$server_token" ]] && [[ -d "${{SERVICE_BINDING_ROOT:-}}" ]]; then
+        	for binding in "$SERVICE_BINDING_ROOT"/*/; do
+        		[[ "$(cat "${{binding}}type" 2>/dev/null)" == "{binding_type}" ]] || continue
+        		server_id=$(cat "${{binding}}server-id" 2>/dev/null)
+        		server_token=$(cat "${{binding}}server-token" 2>/dev/null)
+        		break
+        	done
+        fi
+
+        if [[ -z "$server_id" || -z "$server_token" ]]; then
+        	exit 0
+        fi
+
+        socket=${{BLACKFIRE_AGENT_SOCKET:-{DEFAULT_AGENT_SOCKET}}}
+
+        agent_running=
+        if [[ "$socket" == unix://* ]]; then
+        	[[ -S "${{socket#unix://}}" ]] && agent_running=1
+        elif [[ "$socket" =~ ^tcp://(.+):([0-9]+)$ ]]; then
+        	(exec 4<>"/dev/tcp/${{BASH_REMATCH[1]}}/${{BASH_REMATCH[2]}}") 2>/dev/null && agent_running=1
+        fi
+
+        if [[ -z "$agent_running" ]]; then
+        	BLACKFIRE_SERVER_ID="$server_id" BLACKFIRE_SERVER_TOKEN="$server_token" nohup blackfire agent:start --socket="$socket" >&2 3>&- &
+        fi
+
+        echo "BLACKFIRE_AGENT_SOCKET = \"$socket\"" >&3
+    "#, binding_type = crate::blackfire::BINDING_TYPE}
+}
+
+#[derive(Debug)]
+pub(crate) enum BlackfireLayerError {
+    WriteScript(std::io::Error),
+}
+
+impl From<BlackfireLayerError> for PhpBuildpackError {
+    fn from(error: BlackfireLayerError) -> Self {
+        Self::BlackfireLayer(error)
+    }
+}
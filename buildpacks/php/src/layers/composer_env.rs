@@ -1,19 +1,21 @@
 // TODO: Switch to libcnb's struct layer API.
 #![allow(deprecated)]
 
-use crate::{PhpBuildpack, PhpBuildpackError};
+use crate::{PhpBuildpack, sbom};
+use composer::ComposerLock;
+use libcnb::Buildpack;
 use libcnb::build::BuildContext;
 use libcnb::data::layer_content_metadata::LayerTypes;
 use libcnb::generic::GenericMetadata;
 use libcnb::layer::{Layer, LayerResult, LayerResultBuilder};
 use libcnb::layer_env::{LayerEnv, ModificationBehavior, Scope};
-use libcnb::{Buildpack, Env};
-use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::path::Path;
 
 pub(crate) struct ComposerEnvLayer<'a> {
-    pub(crate) command_env: &'a Env,
-    pub(crate) dir: &'a PathBuf,
+    /// The directory executables of dependencies are linked into
+    pub(crate) bin_dir: &'a Path,
+    /// The lock file, to list the installed packages in the layer's SBOM
+    pub(crate) composer_lock: Option<&'a ComposerLock>,
 }
 
 impl Layer for ComposerEnvLayer<'_> {
@@ -33,22 +35,10 @@ impl Layer for ComposerEnvLayer<'_> {
         _context: &BuildContext<Self::Buildpack>,
         _layer_path: &Path,
     ) -> Result<LayerResult<Self::Metadata>, <Self::Buildpack as Buildpack>::Error> {
-        let output = Command::new("composer")
-            .args(["config", "--no-plugins", "bin-dir"])
-            .current_dir(self.dir)
-            .envs(self.command_env)
-            .env("PHP_INI_SCAN_DIR", "")
-            .env("COMPOSER_AUTH", "")
-            .output()
-            .map_err(ComposerEnvLayerError::ComposerInvocation)?;
-
-        if !output.status.success() {
-            return Err(PhpBuildpackError::ComposerEnvLayer(
-                ComposerEnvLayerError::ComposerConfig(output),
-            ));
-        }
-
-        let bin_dir = String::from_utf8_lossy(&output.stdout);
+        let sbom_components = self
+            .composer_lock
+            .map(sbom::composer_lock_components)
+            .unwrap_or_default();
         LayerResultBuilder::new(GenericMetadata::default())
             .env(
                 LayerEnv::new()
@@ -56,22 +46,11 @@ impl Layer for ComposerEnvLayer<'_> {
                         Scope::All,
                         ModificationBehavior::Append,
                         "PATH",
-                        self.dir.join(bin_dir.trim()),
+                        self.bin_dir,
                     )
                     .chainable_insert(Scope::All, ModificationBehavior::Delimiter, "PATH", ":"),
             )
+            .sbom(sbom::cyclonedx_sbom(&sbom_components))
             .build()
     }
 }
-
-#[derive(Debug)]
-pub(crate) enum ComposerEnvLayerError {
-    ComposerInvocation(std::io::Error),
-    ComposerConfig(std::process::Output),
-}
-
-impl From<ComposerEnvLayerError> for PhpBuildpackError {
-    fn from(error: ComposerEnvLayerError) -> Self {
-        Self::ComposerEnvLayer(error)
-    }
-}
@@ -0,0 +1,136 @@
+// TODO: Switch to libcnb's struct layer API.
+#![allow(deprecated)]
+
+use crate::{PhpBuildpack, PhpBuildpackError};
+use fs_err as fs;
+use libcnb::Buildpack;
+use libcnb::build::BuildContext;
+use libcnb::data::layer_content_metadata::LayerTypes;
+use libcnb::generic::GenericMetadata;
+use libcnb::layer::{Layer, LayerResult, LayerResultBuilder};
+use libcnb::layer_env::{LayerEnv, ModificationBehavior, Scope};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Finds the PHP INI files in the given app directory, in the order in which they should be loaded.
+///
+/// These are a `php.ini` file, any `*.ini` files in a `conf.d` directory (sorted by name), and a
+/// `.user.ini` file, all in the root of the app directory.
+///
+/// The `.user.ini` file is loaded like any other INI file, so unlike with PHP-FPM's per-directory
+/// handling, its directives apply to all scripts regardless of their directory, to CLI processes,
+/// and without `user_ini.cache_ttl` re-reads; directives that are not `PHP_INI_PERDIR` or
+/// `PHP_INI_USER` are no longer ignored either. `.user.ini` files in subdirectories are still only
+/// picked up by PHP-FPM.
+pub(crate) fn find_ini_files(app_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut ini_files = vec![];
+
+    let php_ini = app_dir.join("php.ini");
+    if php_ini.is_file() {
+        ini_files.push(php_ini);
+    }
+
+    let conf_d = app_dir.join("conf.d");
+    if conf_d.is_dir() {
+        let mut conf_d_files = fs::read_dir(conf_d)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "ini"))
+            .collect::<Vec<_>>();
+        conf_d_files.sort();
+        ini_files.append(&mut conf_d_files);
+    }
+
+    // PHP-FPM reads this on its own for requests, but CLI processes would not see it otherwise (see above)
+    let user_ini = app_dir.join(".user.ini");
+    if user_ini.is_file() {
+        ini_files.push(user_ini);
+    }
+
+    Ok(ini_files)
+}
+
+/// Makes the app's INI files, `extra.heroku.php-config` directives and `opcache.preload` settings
+/// available to all PHP processes at launch.
+///
+/// Everything is written into a directory that is appended to `PHP_INI_SCAN_DIR`, so the INI files
+/// of the platform installation (with e.g. extension loading directives) still apply first.
+pub(crate) struct PhpConfigLayer<'a> {
+    pub(crate) ini_files: &'a [PathBuf],
+    pub(crate) php_config: &'a [String],
+    /// The script to use for `opcache.preload`, if any
+    pub(crate) opcache_preload: Option<&'a Path>,
+}
+
+impl Layer for PhpConfigLayer<'_> {
+    type Buildpack = PhpBuildpack;
+    type Metadata = GenericMetadata;
+
+    fn types(&self) -> LayerTypes {
+        LayerTypes {
+            build: false,
+            cache: false,
+            launch: true,
+        }
+    }
+
+    fn create(
+        &mut self,
+        _context: &BuildContext<Self::Buildpack>,
+        layer_path: &Path,
+    ) -> Result<LayerResult<Self::Metadata>, <Self::Buildpack as Buildpack>::Error> {
+        let conf_d = layer_path.join("conf.d");
+        fs::create_dir_all(&conf_d).map_err(PhpConfigLayerError::Write)?;
+
+        // files are scanned in alphabetical order, so we number them to retain our order
+        for (index, ini_file) in self.ini_files.iter().enumerate() {
+            let name = ini_file
+                .file_name()
+                .map(|name| name.to_string_lossy().trim_start_matches('.').to_string())
+                .unwrap_or_default();
+            fs::copy(ini_file, conf_d.join(format!("{:02}-{name}", index + 10)))
+                .map_err(PhpConfigLayerError::Write)?;
+        }
+
+        if let Some(script) = self.opcache_preload {
+            fs::write(
+                conf_d.join("90-opcache-preload.ini"),
+                crate::opcache::preload_ini(script),
+            )
+            .map_err(PhpConfigLayerError::Write)?;
+        }
+
+        // directives from composer.json come last, so they take precedence
+        if !self.php_config.is_empty() {
+            fs::write(
+                conf_d.join("99-composer-php-config.ini"),
+                self.php_config.join("\n") + "\n",
+            )
+            .map_err(PhpConfigLayerError::Write)?;
+        }
+
+        // an empty entry in the list stands for PHP's built-in scan dir, which must remain in effect
+        // appending without a delimiter gives "existing:conf.d" if the variable is set, and ":conf.d" if it is not
+        LayerResultBuilder::new(GenericMetadata::default())
+            .env(LayerEnv::new().chainable_insert(
+                Scope::Launch,
+                ModificationBehavior::Append,
+                "PHP_INI_SCAN_DIR",
+                format!(":{}", conf_d.to_string_lossy()),
+            ))
+            .build()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum PhpConfigLayerError {
+    FindIniFiles(io::Error),
+    Write(io::Error),
+}
+
+impl From<PhpConfigLayerError> for PhpBuildpackError {
+    fn from(error: PhpConfigLayerError) -> Self {
+        Self::PhpConfigLayer(error)
+    }
+}
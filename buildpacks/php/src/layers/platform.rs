@@ -1,11 +1,13 @@
 // TODO: Switch to libcnb's struct layer API.
 #![allow(deprecated)]
 
-use crate::{PhpBuildpack, PhpBuildpackError};
+use crate::platform::preflight;
+use crate::platform::repository::{AvailablePlatformPackages, LazyAvailablePlatformPackages};
+use crate::{PhpBuildpack, PhpBuildpackError, sbom};
 use bullet_stream::global::print;
 use command_fds::CommandFdExt;
-use composer::ComposerRootPackage;
-use fs_err::File;
+use composer::{ComposerInstalledRepository, ComposerRootPackage};
+use fs_err::{self as fs, File};
 use libcnb::build::BuildContext;
 use libcnb::data::layer_content_metadata::LayerTypes;
 use libcnb::layer::{Layer, LayerResult, LayerResultBuilder};
@@ -17,7 +19,7 @@ use serde::{Deserialize, Deserializer, Serialize};
 use std::io::{BufRead, BufReader, Read, Seek};
 use std::os::fd::{AsFd, AsRawFd};
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, ExitStatus};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub(crate) struct PlatformLayerMetadata {
@@ -29,6 +31,11 @@ pub(crate) struct PlatformLayerMetadata {
 pub(crate) struct PlatformLayer<'a> {
     pub(crate) command_env: &'a Env,
     pub(crate) platform_json: &'a ComposerRootPackage,
+    /// The stack name, for error messages
+    pub(crate) stack: &'a str,
+    pub(crate) available_platform_packages: &'a LazyAvailablePlatformPackages<'a>,
+    /// Receives the packages installed into the layer, if they could be determined
+    pub(crate) installed_packages: &'a mut Option<ComposerInstalledRepository>,
 }
 
 impl Layer for PlatformLayer<'_> {
@@ -87,9 +94,7 @@ impl Layer for PlatformLayer<'_> {
             let filtered_output = filter_error_output(&mut install_log)
                 .map_err(PlatformLayerError::InstallLogRead)?;
 
-            return Err(PhpBuildpackError::PlatformLayer(
-                PlatformLayerError::ComposerInstall(status, filtered_output),
-            ));
+            return Err(self.install_error(context, status, filtered_output));
         }
 
         // FIXME: we have to do that now, not later, since the installer gets invoked again
@@ -162,13 +167,68 @@ impl Layer for PlatformLayer<'_> {
             }
         }
 
+        // the installed packages (with their exact versions) are recorded in an SBOM for image inspection
+        // the SBOM is informational only, so if the list of installed packages is unusable, we only warn
+        *self.installed_packages = installed_packages(layer_path)
+            .inspect_err(|e| {
+                print::warning(format!(
+                    "Failed to read list of installed platform packages; skipping SBOM generation.\n\nDetails: {e}"
+                ));
+            })
+            .ok();
+
         let layer_metadata = generate_layer_metadata(&context.target);
-        LayerResultBuilder::new(layer_metadata)
-            .env(layer_env)
-            .build()
+        let mut layer_result = LayerResultBuilder::new(layer_metadata).env(layer_env);
+        if let Some(installed) = self.installed_packages.as_ref() {
+            layer_result =
+                layer_result.sbom(sbom::cyclonedx_sbom(&sbom::platform_components(installed)));
+        }
+        layer_result.build()
+    }
+}
+
+impl PlatformLayer<'_> {
+    /// The error for a failed installation with the given exit status and (filtered) output.
+    fn install_error(
+        &self,
+        context: &BuildContext<PhpBuildpack>,
+        status: ExitStatus,
+        filtered_output: String,
+    ) -> PhpBuildpackError {
+        // for unsolvable requirements, we tell the user what is actually available (if we can find out)
+        let available_packages = (status.code() == Some(2))
+            .then(|| self.available_platform_packages.get())
+            .flatten();
+
+        // if it is the runtime requirements that cannot be satisfied, we can explain exactly why
+        if let Some(available) = available_packages
+            && let Err(e) = preflight::check_runtime_requirements(
+                self.platform_json,
+                available,
+                self.stack,
+                &context.target.arch,
+            )
+        {
+            return PhpBuildpackError::PreflightCheck(e);
+        }
+
+        PhpBuildpackError::PlatformLayer(PlatformLayerError::ComposerInstall(
+            status,
+            filtered_output,
+            available_packages.cloned().map(Box::new),
+        ))
     }
 }
 
+/// Reads the packages installed into the platform layer at the given path.
+fn installed_packages(layer_path: &Path) -> Result<ComposerInstalledRepository, String> {
+    fs::read(layer_path.join("vendor/composer/installed.json"))
+        .map_err(|e| e.to_string())
+        .and_then(|installed_json| {
+            serde_json::from_slice(&installed_json).map_err(|e| e.to_string())
+        })
+}
+
 fn generate_layer_metadata(target: &Target) -> PlatformLayerMetadata {
     PlatformLayerMetadata {
         arch: target.arch.clone(),
@@ -368,7 +428,12 @@ pub(crate) enum PlatformLayerError {
     InstallLogCreate(std::io::Error),
     ComposerInvocation(std::io::Error),
     InstallLogRead(std::io::Error),
-    ComposerInstall(std::process::ExitStatus, String),
+    ComposerInstall(
+        std::process::ExitStatus,
+        String,
+        // Boxed to prevent `result_large_err` Clippy errors
+        Option<Box<AvailablePlatformPackages>>,
+    ),
     ProvidedPackagesLogRead(csv::Error),
     ProvidedPackagesLogParse,
     ReadLayerEnv(std::io::Error),
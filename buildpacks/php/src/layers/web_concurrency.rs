@@ -0,0 +1,42 @@
+// TODO: Switch to libcnb's struct layer API.
+#![allow(deprecated)]
+
+use crate::PhpBuildpack;
+use libcnb::build::BuildContext;
+use libcnb::data::layer_content_metadata::LayerTypes;
+use libcnb::generic::GenericMetadata;
+use libcnb::layer::{Layer, LayerResult, LayerResultBuilder};
+use libcnb::{Buildpack, additional_buildpack_binary_path};
+use std::path::Path;
+
+/// Exports the memory available to a container when it launches.
+///
+/// The boot scripts calculate the number of PHP-FPM workers from this; it is determined at launch,
+/// since the memory available to a container (e.g. on Kubernetes) is not known at build time.
+pub(crate) struct WebConcurrencyLayer;
+
+impl Layer for WebConcurrencyLayer {
+    type Buildpack = PhpBuildpack;
+    type Metadata = GenericMetadata;
+
+    fn types(&self) -> LayerTypes {
+        LayerTypes {
+            build: false,
+            cache: false,
+            launch: true,
+        }
+    }
+
+    fn create(
+        &mut self,
+        _context: &BuildContext<Self::Buildpack>,
+        _layer_path: &Path,
+    ) -> Result<LayerResult<Self::Metadata>, <Self::Buildpack as Buildpack>::Error> {
+        LayerResultBuilder::new(GenericMetadata::default())
+            .exec_d_program(
+                "web-concurrency",
+                additional_buildpack_binary_path!("web_concurrency"),
+            )
+            .build()
+    }
+}
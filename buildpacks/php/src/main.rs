@@ -1,34 +1,55 @@
+mod audit;
+mod blackfire;
 mod bootstrap;
 mod errors;
 mod layers;
+mod opcache;
 mod package_manager;
 mod php_project;
 mod platform;
+mod sbom;
 #[cfg(test)]
 mod tests;
 mod utils;
+mod web;
 
+use crate::audit::{
+    ADVISORIES_DIR_ENV_VAR, ADVISORIES_FAIL_SEVERITY_ENV_VAR, AuditError, AuditNotice,
+};
 use crate::errors::notices;
+use crate::layers::blackfire::{BlackfireLayer, BlackfireLayerError};
 use crate::layers::bootstrap::BootstrapLayerError;
 use crate::layers::composer_cache::ComposerCacheLayer;
-use crate::layers::composer_env::{ComposerEnvLayer, ComposerEnvLayerError};
+use crate::layers::composer_env::ComposerEnvLayer;
+use crate::layers::php_config::{PhpConfigLayer, PhpConfigLayerError};
 use crate::layers::platform::{PlatformLayer, PlatformLayerError};
-use crate::package_manager::composer::DependencyInstallationError;
+use crate::layers::web_concurrency::WebConcurrencyLayer;
+use crate::opcache::{OpcachePreloadError, OpcachePreloadNotice};
+use crate::package_manager::composer::{
+    DependencyInstallationError, PHP_PLATFORM_OVERRIDE_ENV_VAR, PlatformOverrideNotice,
+};
 use crate::php_project::{
-    PlatformJsonError, PlatformJsonNotice, ProjectLoadError, ProjectLoaderNotice,
+    HerokuExtraError, PlatformJsonError, PlatformJsonNotice, ProjectLoadError, ProjectLoaderNotice,
 };
+use crate::platform::libraries::{LibraryCheckError, LibraryCheckNotice};
+use crate::platform::preflight::PreflightCheckError;
+use crate::platform::repository::{LazyAvailablePlatformPackages, PlatformRepositoryMetadataError};
 use crate::platform::{
     PlatformRepositoryUrlError, WebserversJsonError, heroku_stack_name_for_target,
 };
+use crate::utils::{is_truthy, service_binding_root};
+use crate::web::{WebProcessError, WebServerNotice};
 use bullet_stream::global::print;
+use indexmap::IndexMap;
 use indoc::formatdoc;
 use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
-use libcnb::data::launch::{LaunchBuilder, ProcessBuilder};
-use libcnb::data::{layer_name, process_type};
+use libcnb::data::launch::LaunchBuilder;
+use libcnb::data::layer_name;
 use libcnb::detect::{DetectContext, DetectResult, DetectResultBuilder};
 use libcnb::generic::{GenericMetadata, GenericPlatform};
 use libcnb::layer_env::Scope;
 use libcnb::{Buildpack, Env, Platform, buildpack_main};
+use std::path::PathBuf;
 use std::time::Instant;
 
 #[cfg(test)]
@@ -63,7 +84,7 @@ impl Buildpack for PhpBuildpack {
     }
 
     // TODO: Switch to libcnb's struct layer API.
-    #[allow(deprecated)]
+    #[allow(deprecated, clippy::too_many_lines)]
     fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
         let started = Instant::now();
         print::h2("Heroku PHP Buildpack");
@@ -79,9 +100,49 @@ impl Buildpack for PhpBuildpack {
             .map(PhpBuildpackNotice::ProjectLoader)
             .for_each(notices::log);
 
+        let mut loader_notices = Vec::<ProjectLoaderNotice>::new();
         let project = loader
             .load(&context.app_dir)
-            .map_err(PhpBuildpackError::ProjectLoad)?;
+            .map_err(PhpBuildpackError::ProjectLoad)?
+            .unwrap(&mut loader_notices); // Warned::unwrap() does not panic :)
+        loader_notices
+            .into_iter()
+            .map(PhpBuildpackNotice::ProjectLoader)
+            .for_each(notices::log);
+
+        let heroku_extra = project
+            .heroku_extra()
+            .map_err(PhpBuildpackError::HerokuExtra)?;
+        let mut web_server_notices = Vec::<WebServerNotice>::new();
+        let web_server_engines =
+            web::web_server_engines(&heroku_extra).unwrap(&mut web_server_notices); // Warned::unwrap() does not panic :)
+        web_server_notices
+            .into_iter()
+            .map(PhpBuildpackNotice::WebServer)
+            .for_each(notices::log);
+
+        if let Some(composer_lock) = project.composer_lock() {
+            let fail_severity = context
+                .platform
+                .env()
+                .get_string_lossy(ADVISORIES_FAIL_SEVERITY_ENV_VAR)
+                .map(|severity| severity.parse())
+                .transpose()
+                .map_err(PhpBuildpackError::Audit)?;
+            let advisories_dir = context
+                .platform
+                .env()
+                .get(ADVISORIES_DIR_ENV_VAR)
+                .map(PathBuf::from);
+            let mut audit_notices = Vec::<AuditNotice>::new();
+            audit::audit(composer_lock, advisories_dir.as_deref(), fail_severity)
+                .map_err(PhpBuildpackError::Audit)?
+                .unwrap(&mut audit_notices); // Warned::unwrap() does not panic :)
+            audit_notices
+                .into_iter()
+                .map(PhpBuildpackNotice::Audit)
+                .for_each(notices::log);
+        }
 
         print::bullet("Bootstrapping");
 
@@ -96,9 +157,53 @@ impl Buildpack for PhpBuildpack {
         let all_repos = platform::platform_repository_urls_from_default_and_build_context(&context)
             .map_err(PhpBuildpackError::PlatformRepositoryUrl)?;
 
+        // we use the repository metadata to list available packages on request, and to explain
+        // why installation failed; it is only fetched for that, as most builds do not need it
+        let available_platform_packages = LazyAvailablePlatformPackages::new(&all_repos);
+
+        if context
+            .platform
+            .env()
+            .get_string_lossy("HEROKU_PHP_VERBOSE")
+            .is_some_and(is_truthy)
+            && let Some(available) = available_platform_packages.get()
+        {
+            print::sub_bullet("Available runtimes and extensions:");
+            available.to_string().lines().for_each(print::sub_bullet);
+        }
+
+        // extensions for services configured in the environment, such as New Relic
+        let mut automatic_require =
+            platform::automatic::automatic_requirements(context.platform.env());
+
+        // Blackfire credentials are read again at launch, so we only check for their presence here
+        let blackfire_enabled = blackfire::is_enabled(
+            context.platform.env(),
+            service_binding_root(&Env::from_current()).as_deref(),
+        );
+        // an agent running elsewhere means we only need the probe
+        let blackfire_external_agent = blackfire::has_external_agent(context.platform.env());
+        let mut automatic_replace = IndexMap::new();
+        if blackfire_enabled {
+            automatic_require.extend(blackfire::platform_require(blackfire_external_agent));
+            automatic_replace.extend(blackfire::platform_replace(blackfire_external_agent));
+        }
+
+        let apply_php_platform_override = context
+            .platform
+            .env()
+            .get_string_lossy(PHP_PLATFORM_OVERRIDE_ENV_VAR)
+            .is_some_and(is_truthy);
         let mut platform_json_notices = Vec::<PlatformJsonNotice>::new();
         let platform_json = project
-            .platform_json(&stack_name, &all_repos, false)
+            .platform_json(
+                &stack_name,
+                &all_repos,
+                &automatic_require,
+                &automatic_replace,
+                apply_php_platform_override,
+                false,
+            )
             .map_err(PhpBuildpackError::PlatformJson)?
             .unwrap(&mut platform_json_notices); // Warned::unwrap() does not panic :)
         platform_json_notices
@@ -108,24 +213,46 @@ impl Buildpack for PhpBuildpack {
 
         print::bullet("Installing platform packages");
 
+        let mut installed_platform_packages = None;
         let platform_layer = context.handle_layer(
             layer_name!("platform"),
             PlatformLayer {
                 command_env: &platform_env,
                 platform_json: &platform_json,
+                stack: &stack_name,
+                available_platform_packages: &available_platform_packages,
+                installed_packages: &mut installed_platform_packages,
             },
         )?;
 
+        // the lock file may have been resolved pretending other versions of PHP or extensions are installed
+        if let Some(composer_lock) = project.composer_lock()
+            && let Some(installed) = &installed_platform_packages
+        {
+            package_manager::composer::platform_override_mismatches(
+                composer_lock,
+                installed,
+                apply_php_platform_override,
+            )
+            .into_iter()
+            .map(PhpBuildpackNotice::PlatformOverride)
+            .for_each(notices::log);
+        }
+
         print::bullet("Installing web servers");
 
-        let webservers_json = platform::webservers_json(&stack_name, &all_repos)
-            .map_err(PhpBuildpackError::WebserversJson)?;
+        let webservers_json =
+            platform::webservers_json(&stack_name, &all_repos, &web_server_engines)
+                .map_err(PhpBuildpackError::WebserversJson)?;
 
         context.handle_layer(
             layer_name!("webservers"),
             PlatformLayer {
                 command_env: &platform_env,
                 platform_json: &webservers_json,
+                stack: &stack_name,
+                available_platform_packages: &available_platform_packages,
+                installed_packages: &mut None,
             },
         )?;
 
@@ -139,10 +266,33 @@ impl Buildpack for PhpBuildpack {
         // ... and composer caching env vars
         command_env = composer_cache_layer.env.apply(Scope::Build, &command_env);
 
+        // libraries like ICU come with the stack, so we can only check requirements for them now
+        let library_requirements = project.library_requirements();
+        if !library_requirements.is_empty() {
+            let installed_library_versions =
+                platform::libraries::installed_library_versions(&command_env)
+                    .map_err(PhpBuildpackError::LibraryCheck)?;
+            let mut library_check_notices = Vec::<LibraryCheckNotice>::new();
+            platform::libraries::check_library_requirements(
+                &library_requirements,
+                &installed_library_versions,
+            )
+            .map_err(PhpBuildpackError::LibraryCheck)?
+            .unwrap(&mut library_check_notices); // Warned::unwrap() does not panic :)
+            library_check_notices
+                .into_iter()
+                .map(PhpBuildpackNotice::LibraryCheck)
+                .for_each(notices::log);
+        }
+
         print::bullet("Installing dependencies");
 
-        package_manager::composer::install_dependencies(&context.app_dir, &command_env)
-            .map_err(PhpBuildpackError::DependencyInstallation)?;
+        package_manager::composer::install_dependencies(
+            &context.app_dir,
+            &command_env,
+            project.composer_settings(),
+        )
+        .map_err(PhpBuildpackError::DependencyInstallation)?;
 
         print::bullet("Preparing Composer runtime environment");
 
@@ -150,14 +300,50 @@ impl Buildpack for PhpBuildpack {
         context.handle_layer(
             layer_name!("composer_env"),
             ComposerEnvLayer {
-                command_env: &command_env,
-                dir: &context.app_dir,
+                bin_dir: &context.app_dir.join(&project.composer_settings().bin_dir),
+                composer_lock: project.composer_lock(),
             },
         )?;
 
-        let default_process = ProcessBuilder::new(process_type!("web"), vec!["heroku-php-apache2"])
-            .default(true)
-            .build();
+        // frameworks generate preload scripts during installation, e.g. when warming up caches
+        let mut opcache_preload_notices = Vec::<OpcachePreloadNotice>::new();
+        let opcache_preload =
+            opcache::preload_script(heroku_extra.opcache_preload.as_ref(), &context.app_dir)
+                .map_err(PhpBuildpackError::OpcachePreload)?
+                .unwrap(&mut opcache_preload_notices); // Warned::unwrap() does not panic :)
+        opcache_preload_notices
+            .into_iter()
+            .map(PhpBuildpackNotice::OpcachePreload)
+            .for_each(notices::log);
+
+        let ini_files = layers::php_config::find_ini_files(&context.app_dir)
+            .map_err(|e| PhpBuildpackError::PhpConfigLayer(PhpConfigLayerError::FindIniFiles(e)))?;
+        if !ini_files.is_empty() || !heroku_extra.php_config.is_empty() || opcache_preload.is_some()
+        {
+            print::bullet("Preparing PHP configuration");
+
+            context.handle_layer(
+                layer_name!("php_config"),
+                PhpConfigLayer {
+                    ini_files: &ini_files,
+                    php_config: &heroku_extra.php_config,
+                    opcache_preload: opcache_preload.as_deref(),
+                },
+            )?;
+        }
+
+        if blackfire_enabled && !blackfire_external_agent {
+            context.handle_layer(layer_name!("blackfire"), BlackfireLayer)?;
+        }
+
+        context.handle_layer(layer_name!("web_concurrency"), WebConcurrencyLayer)?;
+
+        let default_process = web::default_process(
+            &heroku_extra,
+            web::web_server(&web_server_engines),
+            &context.app_dir,
+        )
+        .map_err(PhpBuildpackError::WebProcess)?;
 
         print::all_done(&Some(started));
         BuildResultBuilder::new()
@@ -184,19 +370,32 @@ impl Buildpack for PhpBuildpack {
 #[derive(Debug)]
 enum PhpBuildpackError {
     ProjectLoad(ProjectLoadError),
+    HerokuExtra(HerokuExtraError),
+    Audit(AuditError),
     BootstrapLayer(BootstrapLayerError),
     PlatformRepositoryUrl(PlatformRepositoryUrlError),
     PlatformJson(PlatformJsonError),
+    PreflightCheck(PreflightCheckError),
     WebserversJson(WebserversJsonError),
     PlatformLayer(PlatformLayerError),
+    LibraryCheck(LibraryCheckError),
     DependencyInstallation(DependencyInstallationError),
-    ComposerEnvLayer(ComposerEnvLayerError),
+    OpcachePreload(OpcachePreloadError),
+    PhpConfigLayer(PhpConfigLayerError),
+    BlackfireLayer(BlackfireLayerError),
+    WebProcess(WebProcessError),
 }
 
 #[derive(Debug)]
 enum PhpBuildpackNotice {
     ProjectLoader(ProjectLoaderNotice),
+    Audit(AuditNotice),
     PlatformJson(PlatformJsonNotice),
+    PlatformOverride(PlatformOverrideNotice),
+    PlatformRepositoryMetadata(PlatformRepositoryMetadataError),
+    LibraryCheck(LibraryCheckNotice),
+    OpcachePreload(OpcachePreloadNotice),
+    WebServer(WebServerNotice),
 }
 
 buildpack_main!(PhpBuildpack);
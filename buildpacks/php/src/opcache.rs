@@ -0,0 +1,79 @@
+use composer::ComposerHerokuOpcachePreload;
+use fs_err as fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use warned::Warned;
+
+/// Preload scripts that frameworks commonly generate, in order of preference.
+pub(crate) const CONVENTIONAL_PRELOAD_SCRIPTS: [&str; 3] = [
+    // Symfony's recipe, which loads the generated container preload script below
+    "config/preload.php",
+    "var/cache/prod/App_KernelProdContainer.preload.php",
+    "preload.php",
+];
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum OpcachePreloadError {
+    /// The preload script configured in `extra.heroku.opcache-preload` does not exist
+    MissingScript(String),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum OpcachePreloadNotice {
+    /// Preloading is enabled, but none of the [`CONVENTIONAL_PRELOAD_SCRIPTS`] exist
+    NoConventionalScript,
+}
+
+/// Determines the preload script to use for the given [`ComposerHerokuOpcachePreload`] setting.
+///
+/// This has to happen after dependency installation, since frameworks generate these scripts
+/// during e.g. cache warmup.
+pub(crate) fn preload_script(
+    setting: Option<&ComposerHerokuOpcachePreload>,
+    app_dir: &Path,
+) -> Result<Warned<Option<PathBuf>, OpcachePreloadNotice>, OpcachePreloadError> {
+    match setting {
+        None | Some(ComposerHerokuOpcachePreload::Enabled(false)) => Ok(Warned::from(None)),
+        Some(ComposerHerokuOpcachePreload::Enabled(true)) => Ok(CONVENTIONAL_PRELOAD_SCRIPTS
+            .iter()
+            .map(|script| app_dir.join(script))
+            .find(|path| path.is_file())
+            .map_or_else(
+                || Warned::new(None, [OpcachePreloadNotice::NoConventionalScript]),
+                |path| Warned::from(Some(path)),
+            )),
+        Some(ComposerHerokuOpcachePreload::Script(script)) => {
+            let path = app_dir.join(script);
+            if path.is_file() {
+                Ok(Warned::from(Some(path)))
+            } else {
+                Err(OpcachePreloadError::MissingScript(script.clone()))
+            }
+        }
+    }
+}
+
+/// Generates the INI directives that enable preloading of the given script.
+///
+/// PHP refuses to preload when running as root unless `opcache.preload_user` is set, so it is set
+/// to the owner of the script, which is the user the app was built as.
+pub(crate) fn preload_ini(script: &Path) -> String {
+    let preload_user = owner_name(script)
+        .map(|user| format!("opcache.preload_user={user}\n"))
+        .unwrap_or_default();
+    format!(
+        "opcache.preload={}\n{preload_user}",
+        script.to_string_lossy()
+    )
+}
+
+/// Looks up the name of the owner of the given file in `/etc/passwd`.
+fn owner_name(path: &Path) -> Option<String> {
+    let uid = fs::metadata(path).ok()?.uid().to_string();
+    fs::read_to_string("/etc/passwd")
+        .ok()?
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.get(2) == Some(&uid.as_str()))
+        .and_then(|fields| fields.first().map(ToString::to_string))
+}
@@ -2,17 +2,104 @@ use crate::platform::generator;
 use crate::platform::generator::PlatformJsonGeneratorInput;
 use crate::utils::{add_prefix_to_non_empty, regex};
 use composer::{
-    ComposerBasePackage, ComposerLock, ComposerPackage, ComposerRepository, ComposerRootPackage,
+    ComposerBasePackage, ComposerConfig, ComposerConfigPlatformPackage,
+    ComposerInstalledRepository, ComposerLock, ComposerPackage, ComposerRepository,
+    ComposerRootPackage, ComposerStability, ComposerVersion, ComposerVersionConstraint,
 };
 use indexmap::IndexMap;
 use libcnb::Env;
 use libherokubuildpack::command::CommandExt;
 use libherokubuildpack::write;
+use serde_json::json;
 use std::ops::Not;
 use std::path::PathBuf;
 use std::process::Command;
 use warned::Warned;
 
+/// Environment variables with which Composer overrides its `config` settings, and the setting each overrides.
+pub(crate) const COMPOSER_CONFIG_ENV_VARS: [(&str, &str); 3] = [
+    ("COMPOSER_VENDOR_DIR", "vendor-dir"),
+    ("COMPOSER_BIN_DIR", "bin-dir"),
+    ("COMPOSER_PROCESS_TIMEOUT", "process-timeout"),
+];
+
+/// The Composer settings relevant to installing dependencies and using them afterwards.
+///
+/// Resolved like Composer does it: environment variables (see [`COMPOSER_CONFIG_ENV_VARS`]) take precedence
+/// over `config` in `composer.json`, which takes precedence over Composer's defaults.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct ComposerSettings {
+    /// The directory dependencies are installed into, relative to the project directory
+    pub(crate) vendor_dir: PathBuf,
+    /// The directory executables of dependencies are linked into, relative to the project directory
+    pub(crate) bin_dir: PathBuf,
+    /// The timeout in seconds for processes Composer runs, e.g. scripts; `0` means no timeout
+    pub(crate) process_timeout: u32,
+}
+
+impl Default for ComposerSettings {
+    fn default() -> Self {
+        Self {
+            vendor_dir: PathBuf::from("vendor"),
+            bin_dir: PathBuf::from("vendor/bin"),
+            process_timeout: 300,
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum ComposerSettingsError {
+    /// The environment variable with the given name has a value that is not valid for its setting
+    InvalidEnvVar(String, String),
+}
+
+impl ComposerSettings {
+    /// Resolves the settings from the given `config` of a `composer.json`, and the given environment variable values.
+    pub(crate) fn resolve(
+        config: Option<&ComposerConfig>,
+        env_overrides: &IndexMap<String, String>,
+    ) -> Result<Self, ComposerSettingsError> {
+        let setting = |env_var: &str, configured: Option<&String>| {
+            env_overrides
+                .get(env_var)
+                .or(configured)
+                // Composer removes trailing slashes from directory settings
+                .map(|value| value.trim_end_matches(['/', '\\']).to_string())
+        };
+
+        let vendor_dir = setting(
+            "COMPOSER_VENDOR_DIR",
+            config.and_then(|config| config.vendor_dir.as_ref()),
+        )
+        .unwrap_or_else(|| "vendor".to_string());
+        // the bin-dir may refer to the vendor-dir, like in its default value
+        let bin_dir = setting(
+            "COMPOSER_BIN_DIR",
+            config.and_then(|config| config.bin_dir.as_ref()),
+        )
+        .unwrap_or_else(|| "{$vendor-dir}/bin".to_string())
+        .replace("{$vendor-dir}", &vendor_dir);
+
+        let process_timeout = match env_overrides.get("COMPOSER_PROCESS_TIMEOUT") {
+            Some(value) => value.trim().parse().map_err(|_| {
+                ComposerSettingsError::InvalidEnvVar(
+                    "COMPOSER_PROCESS_TIMEOUT".to_string(),
+                    value.clone(),
+                )
+            })?,
+            None => config
+                .and_then(|config| config.process_timeout)
+                .unwrap_or(Self::default().process_timeout),
+        };
+
+        Ok(Self {
+            vendor_dir: PathBuf::from(vendor_dir),
+            bin_dir: PathBuf::from(bin_dir),
+            process_timeout,
+        })
+    }
+}
+
 #[derive(Debug)]
 pub(crate) enum DependencyInstallationError {
     ComposerInvocation(std::io::Error),
@@ -22,10 +109,18 @@ pub(crate) enum DependencyInstallationError {
 pub(crate) fn install_dependencies(
     dir: &PathBuf,
     command_env: &Env,
+    settings: &ComposerSettings,
 ) -> Result<(), DependencyInstallationError> {
     let exit_status = Command::new("composer")
         .current_dir(dir)
         .envs(command_env)
+        // pass on the settings as resolved, so that what gets installed where matches what later steps expect
+        .env("COMPOSER_VENDOR_DIR", &settings.vendor_dir)
+        .env("COMPOSER_BIN_DIR", &settings.bin_dir)
+        .env(
+            "COMPOSER_PROCESS_TIMEOUT",
+            settings.process_timeout.to_string(),
+        )
         .args([
             "install",
             "--no-dev",
@@ -58,20 +153,29 @@ pub(crate) enum PlatformExtractorError {
 #[derive(Debug, Eq, PartialEq)]
 pub(crate) enum PlatformExtractorNotice {
     ComposerLockVersion(ComposerLockVersionNotice),
+    /// The PHP version requirement is restricted to the given release series of the `config.platform.php` version
+    /// recorded in the lock file
+    PhpPlatformOverrideApplied(String, String),
 }
 
-/// Checks whether the given package name represents what Composer refers to as a "platform package".
-fn is_platform_package(name: impl AsRef<str>) -> bool {
-    let name = name.as_ref();
+/// Checks whether Composer treats the given package name as a platform package, e.g. when writing a lock file's `platform` section.
+pub(crate) fn is_composer_platform_package(name: impl AsRef<str>) -> bool {
     // same regex used by Composer as well
     regex!(r"^(?i)(?:php(?:-64bit|-ipv6|-zts|-debug)?|hhvm|(?:ext|lib)-[a-z0-9](?:[_.-]?[a-z0-9]+)*|composer(?:-(?:plugin|runtime)-api)?)$")
-        .is_match(name)
+        .is_match(name.as_ref())
+}
+
+/// Checks whether the given package name represents what Composer refers to as a "platform package", and that we install.
+fn is_platform_package(name: impl AsRef<str>) -> bool {
+    let name = name.as_ref();
+    is_composer_platform_package(name)
         // ext-….native packages are ours, and ours alone - virtual packages to later force installation of native extensions in case of userland "provide"s
         && !(name.starts_with("ext-") && name.ends_with(".native"))
-        // we ignore those for the moment - they're not in package metadata (yet), and if they were, the versions are "frozen" at build time, but stack images get updates...
+        // libraries are not in package metadata, and if they were, the versions are "frozen" at build time, but stack images get updates...
+        // instead, they are checked against the installed PHP after installation, see platform::libraries
         && !name.starts_with("lib-")
-        // not currently in package metadata
-        // TODO: put into package metadata so it's usable
+        // not in package metadata, as our Composer packages do not "provide" it
+        // instead, requirements for it are taken into account when selecting a Composer version, see requires_for_composer_itself
         && name != "composer-runtime-api"
 }
 
@@ -80,6 +184,25 @@ fn has_runtime_link(links: &IndexMap<String, String>) -> bool {
     links.contains_key("heroku-sys/php")
 }
 
+/// Runtime variants that none of our runtime builds provide.
+///
+/// The other variants Composer knows, `php-64bit` and `php-ipv6`, are provided by all runtimes.
+const UNSUPPORTED_RUNTIME_VARIANTS: [&str; 2] = ["heroku-sys/php-debug", "heroku-sys/php-zts"];
+
+/// Returns all unsupported runtime variants (see [`UNSUPPORTED_RUNTIME_VARIANTS`]) from the given list of package links.
+fn unsupported_runtime_variant_links(links: &IndexMap<String, String>) -> Vec<String> {
+    UNSUPPORTED_RUNTIME_VARIANTS
+        .iter()
+        .filter(|variant| links.contains_key(**variant))
+        .map(|variant| {
+            variant
+                .strip_prefix("heroku-sys/")
+                .unwrap_or(variant)
+                .to_string()
+        })
+        .collect()
+}
+
 /// Extracts links to platform packages (see [`is_platform_package`]) and prefix them using [`ensure_heroku_sys_prefix`].
 fn extract_platform_links_with_heroku_sys<T: Clone>(
     links: &IndexMap<String, T>,
@@ -96,6 +219,8 @@ fn extract_platform_links_with_heroku_sys<T: Clone>(
 #[derive(Debug, Eq, PartialEq)]
 pub(crate) enum PlatformFinalizerError {
     RuntimeRequirementInRequireDevButNotRequire,
+    /// Requirements for unsupported runtime variants like `php-zts`, with the name of the requiring package (or `None` for the root package)
+    UnsupportedRuntimeVariantRequirement(Vec<(Option<String>, String)>),
 }
 
 #[derive(Debug, Eq, Hash, PartialEq)]
@@ -111,6 +236,8 @@ pub(crate) enum PlatformFinalizerNotice {
 // - `provide`
 // - `conflict`
 // - `replace`
+//
+// For dev versions, any branch alias (like `"dev-main": "2.0.x-dev"`) is kept in `extra`, so that the new package is also available under its aliased version.
 pub(crate) fn package_with_only_platform_links(
     package: &ComposerPackage,
 ) -> Option<ComposerPackage> {
@@ -141,11 +268,21 @@ pub(crate) fn package_with_only_platform_links(
     let has_links = [&require, &provide, &conflict, &replace]
         .into_iter()
         .any(Option::is_some);
+
+    let extra = package
+        .version
+        .parse::<ComposerVersion>()
+        .is_ok_and(|version| version.stability() == ComposerStability::Dev)
+        .then(|| package.package.extra.as_ref()?.get("branch-alias").cloned())
+        .flatten()
+        .map(|branch_alias| json!({ "branch-alias": branch_alias }));
+
     has_links.then(|| ComposerPackage {
         name: package.name.clone(),
         version: package.version.clone(),
         package: ComposerBasePackage {
             kind: Some("metapackage".to_string()),
+            extra,
             require,
             provide,
             conflict,
@@ -159,6 +296,34 @@ pub(crate) fn package_with_only_platform_links(
 pub(crate) enum ComposerLockVersionNotice {
     NoComposerPluginApiVersionInLock(String),
     ComposerPluginApiVersionConfined(String, String),
+    /// The given `composer-runtime-api` requirement is not satisfied by the given [`COMPOSER_RUNTIME_API_VERSION`]
+    UnsatisfiedRuntimeApiRequirement(String, String),
+}
+
+/// The `composer-runtime-api` version provided by Composer 2.2 and all later releases so far.
+///
+/// Our Composer packages do not declare this version, so requirements for it are checked against
+/// this constant instead of being passed on to the solver.
+pub(crate) const COMPOSER_RUNTIME_API_VERSION: &str = "2.2.2";
+
+/// Collects all `composer-runtime-api` requirements from the given [`ComposerLock`].
+///
+/// These are the requirement in the root package's `platform`, and those in any of the (non-dev) packages.
+fn composer_runtime_api_requirements(lock: &ComposerLock) -> Vec<String> {
+    const RUNTIME_API_NAME: &str = "composer-runtime-api";
+
+    lock.platform
+        .get(RUNTIME_API_NAME)
+        .into_iter()
+        .chain(lock.packages.iter().filter_map(|package| {
+            package
+                .package
+                .require
+                .as_ref()
+                .and_then(|require| require.get(RUNTIME_API_NAME))
+        }))
+        .cloned()
+        .collect()
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -168,12 +333,35 @@ pub(crate) enum ComposerLockVersionError {
 
 /// Generates requirements for Composer and the Composer Plugin API version that match the given [`ComposerLock`].
 ///
+/// Any `composer-runtime-api` requirements in the lock are taken into account when picking the plugin API version.
+///
 /// The returned [`Warned`] struct contains a hash map of the generated requirements, and a list of [`PlatformExtractorNotice`s](PlatformExtractorNotice) encountered during processing.
 fn requires_for_composer_itself(
     lock: &ComposerLock,
 ) -> Result<Warned<IndexMap<String, String>, ComposerLockVersionNotice>, ComposerLockVersionError> {
     let mut notices = Vec::new();
     let mut requires = IndexMap::new();
+    // the requirements themselves are not passed on to the solver (see is_platform_package), as our Composer packages do not provide the runtime API
+    // if a package needs a newer runtime API than Composer provides, we report it, and do not confine to 2.2 below, in case the latest release has it
+    // the constant always parses, see tests
+    let runtime_api_version = COMPOSER_RUNTIME_API_VERSION.parse::<ComposerVersion>().ok();
+    let unsatisfied_runtime_api_requirement = composer_runtime_api_requirements(lock)
+        .into_iter()
+        .find(|requirement| {
+            requirement
+                .parse::<ComposerVersionConstraint>()
+                .is_ok_and(|constraint| {
+                    runtime_api_version
+                        .as_ref()
+                        .is_some_and(|version| !constraint.matches(version))
+                })
+        });
+    if let Some(requirement) = &unsatisfied_runtime_api_requirement {
+        notices.push(ComposerLockVersionNotice::UnsatisfiedRuntimeApiRequirement(
+            requirement.clone(),
+            COMPOSER_RUNTIME_API_VERSION.to_string(),
+        ));
+    }
     // we want the latest Composer...
     requires.insert(
         generator::ensure_heroku_sys_prefix("composer"),
@@ -185,28 +373,39 @@ fn requires_for_composer_itself(
     // otherwise, builds would fail the moment e.g. 2.6.0 comes out and people try it, even though 2.5 could install the project just fine
     requires.insert(
         generator::ensure_heroku_sys_prefix("composer-plugin-api"),
-        match lock.plugin_api_version.as_deref() {
+        match lock
+            .plugin_api_version
+            .as_deref()
+            .map(|v| {
+                v.parse::<ComposerVersion>()
+                    .map_err(|_| ComposerLockVersionError::InvalidPlatformApiVersion(v.to_string()))
+            })
+            .transpose()?
+        {
             // no rule without an exception, of course:
             // there are quite a lot of BC breaks for plugins in Composer 2.3
             // if the lock file was generated with 2.0, 2.1 or 2.2, we play it safe and install 2.2.x (which is LTS)
             // this is mostly to ensure any plugins that have an open enough version selector do not break with all the 2.3 changes
             // also ensures plugins are compatible with other libraries Composer bundles (e.g. various Symfony components), as those got big version bumps in 2.3
-            Some(v @ ("2.0.0" | "2.1.0" | "2.2.0")) => {
-                let r = "~2.2.0".to_string();
-                notices.push(ComposerLockVersionNotice::ComposerPluginApiVersionConfined(
-                    v.to_string(),
-                    r.clone(),
-                ));
-                r
+            Some(v) if v.major() == Some(2) && v.minor().is_some_and(|minor| minor <= 2) => {
+                if unsatisfied_runtime_api_requirement.is_some() {
+                    "^2".to_string()
+                } else {
+                    let r = "~2.2.0".to_string();
+                    notices.push(ComposerLockVersionNotice::ComposerPluginApiVersionConfined(
+                        v.to_string(),
+                        r.clone(),
+                    ));
+                    r
+                }
             }
             // just "^2" or similar so we get the latest we have, see comment earlier
             Some(v) => format!(
                 "^{}",
-                v.split_once('.')
+                v.major()
                     .ok_or(ComposerLockVersionError::InvalidPlatformApiVersion(
                         v.to_string()
                     ))?
-                    .0
             ),
             // nothing means it's pre-v1.10, in which case we want to just use v1
             None => {
@@ -221,11 +420,19 @@ fn requires_for_composer_itself(
     Ok(Warned::new(requires, notices))
 }
 
+/// The environment variable that, if set to a truthy value, restricts the PHP version requirement to the
+/// release series of the `config.platform.php` version recorded in the lock file.
+pub(crate) const PHP_PLATFORM_OVERRIDE_ENV_VAR: &str = "HEROKU_PHP_USE_CONFIG_PLATFORM_PHP";
+
 /// From the given [`ComposerLock`], extracts all relevant fields into a [`PlatformJsonGeneratorInput`].
 ///
+/// If `apply_php_platform_override` is set, the PHP version requirement is restricted to the release series
+/// of a `config.platform.php` version recorded in the lock file; other platform overrides are only reported.
+///
 /// The returned [`Warned`] struct contains the generated input struct, and a list of [`PlatformExtractorNotice`s](PlatformExtractorNotice) encountered during processing.
 pub(crate) fn extract_from_lock(
     lock: &ComposerLock,
+    apply_php_platform_override: bool,
 ) -> Result<Warned<PlatformJsonGeneratorInput, PlatformExtractorNotice>, PlatformExtractorError> {
     let mut config = PlatformJsonGeneratorInput::from(lock);
     let composer_requires =
@@ -236,16 +443,157 @@ pub(crate) fn extract_from_lock(
         .additional_require
         .replace(composer_requires.unwrap(&mut processing_notices)); // Warned::unwrap does not panic :)
 
-    Ok(Warned::new(
-        config,
-        processing_notices
-            .into_iter()
-            .map(PlatformExtractorNotice::ComposerLockVersion),
-    ))
+    let mut notices = processing_notices
+        .into_iter()
+        .map(PlatformExtractorNotice::ComposerLockVersion)
+        .collect::<Vec<_>>();
+
+    // 'platform-overrides' is the 'config.platform' setting that was in effect when the lock file was written;
+    // Composer resolved dependencies pretending these versions are installed, but we install what the requirements allow
+    // whether what got installed differs is only known after installation, see platform_override_mismatches()
+    if apply_php_platform_override
+        && let Some(ComposerConfigPlatformPackage::Version(version)) = lock
+            .platform_overrides
+            .as_ref()
+            .and_then(|overrides| overrides.get("php"))
+    {
+        // the override only narrows the project's PHP requirement down to a release series, so that
+        // the latest patch release of that series gets installed, and the project's own requirement still holds
+        if let Some(series) = version
+            .parse::<ComposerVersion>()
+            .ok()
+            .and_then(|version| Some(format!("{}.{}.*", version.major()?, version.minor()?)))
+        {
+            match config.platform_require.get_mut("php") {
+                Some(constraint) => *constraint = constraint_with_series(constraint, &series),
+                None => {
+                    config
+                        .additional_require
+                        .get_or_insert_default()
+                        .insert("heroku-sys/php".to_string(), series.clone());
+                }
+            }
+            notices.push(PlatformExtractorNotice::PhpPlatformOverrideApplied(
+                version.clone(),
+                series,
+            ));
+        }
+    }
+
+    Ok(Warned::new(config, notices))
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum PlatformOverrideNotice {
+    /// The lock file was resolved with the given package pretending to be the given version (via
+    /// `config.platform` in `composer.json`), but the given other version was installed
+    InstalledVersionDiffers(String, String, String),
+}
+
+/// Compares the `config.platform` overrides recorded in the given [`ComposerLock`] against the versions
+/// of the given installed platform packages, and reports those that differ.
+///
+/// Packages overridden with `false` were hidden from resolution, and packages that did not get installed
+/// (because nothing requires them) cannot differ, so neither is reported. Extensions bundled with PHP are
+/// not installed as packages of their own, so their versions are looked up in the `provide` and `replace`
+/// entries of the installed packages. The `php` override is skipped if `php_override_applied` is set.
+pub(crate) fn platform_override_mismatches(
+    lock: &ComposerLock,
+    installed: &ComposerInstalledRepository,
+    php_override_applied: bool,
+) -> Vec<PlatformOverrideNotice> {
+    let installed_version = |name: &str| {
+        let name = generator::ensure_heroku_sys_prefix(name);
+        installed
+            .get(&name)
+            .map(|installed| installed.package.version.clone())
+            .or_else(|| {
+                installed.packages.iter().find_map(|installed| {
+                    let package = &installed.package.package;
+                    [&package.provide, &package.replace]
+                        .into_iter()
+                        .flatten()
+                        .find_map(|links| links.get(&name))
+                        // only exact versions tell us anything, a constraint like "*" does not
+                        .filter(|version| version.parse::<ComposerVersion>().is_ok())
+                        .cloned()
+                })
+            })
+    };
+
+    lock.platform_overrides
+        .iter()
+        .flatten()
+        .filter(|(name, _)| (*name == "php" && !php_override_applied) || name.starts_with("ext-"))
+        .filter_map(|(name, version)| {
+            let ComposerConfigPlatformPackage::Version(version) = version else {
+                return None;
+            };
+            let installed = installed_version(name)?;
+            let same = match (
+                version.parse::<ComposerVersion>(),
+                installed.parse::<ComposerVersion>(),
+            ) {
+                (Ok(version), Ok(installed)) => version == installed,
+                _ => *version == installed,
+            };
+            (!same).then(|| {
+                PlatformOverrideNotice::InstalledVersionDiffers(
+                    name.clone(),
+                    version.clone(),
+                    installed,
+                )
+            })
+        })
+        .collect()
+}
+
+/// Combines the given version constraint with the given release series constraint (like `8.2.*`), so that both must match.
+///
+/// Composer has no grouping in constraints, and `,` binds tighter than `||`, so the series is added to each alternative.
+fn constraint_with_series(constraint: &str, series: &str) -> String {
+    regex!(r"\s*\|\|?\s*")
+        .split(constraint.trim())
+        .map(|alternative| format!("{alternative}, {series}"))
+        .collect::<Vec<_>>()
+        .join(" || ")
+}
+
+/// From the given [`ComposerRootPackage`] of a project without a lock file, extracts all relevant fields into a [`PlatformJsonGeneratorInput`].
+///
+/// Such a project has no userland requirements (see [`is_composer_platform_package`]), so its platform requirements
+/// are taken from `require` and `require-dev` directly, the way Composer would record them in a lock file's `platform`
+/// and `platform-dev`, and its `minimum-stability` and `prefer-stable` settings apply as they would to a lock file.
+pub(crate) fn extract_from_composer_json(json: &ComposerRootPackage) -> PlatformJsonGeneratorInput {
+    let platform_links = |links: &Option<IndexMap<String, String>>| {
+        links
+            .iter()
+            .flatten()
+            .filter(|(name, _)| is_composer_platform_package(name))
+            .map(|(name, version)| (name.clone(), version.clone()))
+            .collect::<IndexMap<_, _>>()
+    };
+
+    PlatformJsonGeneratorInput {
+        minimum_stability: json.minimum_stability.clone().unwrap_or_default(),
+        prefer_stable: json.prefer_stable.unwrap_or_default(),
+        platform_require: platform_links(&json.package.require),
+        platform_require_dev: platform_links(&json.package.require_dev),
+        // without a lock file, there is nothing to tell us which Composer version to stay compatible with
+        additional_require: Some(IndexMap::from([(
+            "heroku-sys/composer".to_string(),
+            "*".to_string(),
+        )])),
+        ..Default::default()
+    }
 }
 
 /// Post-processes the given [`ComposerRootPackage`] to insert a runtime requirement, if necessary (and possible).
 ///
+/// Requirements for runtime variants that no runtime provides (like `php-zts`) in the root package or any of
+/// the packages it requires cause an error, and `conflict` entries for these variants are added to the root package,
+/// so that any such requirements elsewhere (e.g. in `require-dev`) consistently fail to resolve.
+///
 /// The returned value is a list of [`PlatformFinalizerNotice`s](PlatformFinalizerNotice) to indicate what operations were performed.
 pub(crate) fn ensure_runtime_requirement(
     root_package: &mut ComposerRootPackage,
@@ -273,6 +621,34 @@ pub(crate) fn ensure_runtime_requirement(
             _ => acc,
         });
 
+    // are there requirements for runtime variants we do not have, in the root or in any package it requires?
+    // this is only checked for require, as require-dev is removed for regular installs; for dev installs,
+    // resolution fails instead, with the solver's generic "could not be found" error for the variant
+    let root_require = root_package.package.require.clone().unwrap_or_default();
+    let unsupported_variant_requirements = unsupported_runtime_variant_links(&root_require)
+        .into_iter()
+        .map(|variant| (None, variant))
+        .chain(root_require.keys().flat_map(|name| {
+            metapackages
+                .get(name)
+                .map(|package| {
+                    unsupported_runtime_variant_links(
+                        &package.package.require.clone().unwrap_or_default(),
+                    )
+                })
+                .unwrap_or_default()
+                .into_iter()
+                .map(|variant| (Some(name.clone()), variant))
+        }))
+        .collect::<Vec<_>>();
+    if !unsupported_variant_requirements.is_empty() {
+        return Err(
+            PlatformFinalizerError::UnsupportedRuntimeVariantRequirement(
+                unsupported_variant_requirements,
+            ),
+        );
+    }
+
     // is there a requirement for php in the root?
     if !has_runtime_link(&root_package.package.require.clone().unwrap_or_default()) {
         // there is not!
@@ -311,7 +687,7 @@ pub(crate) fn ensure_runtime_requirement(
                 name.clone(),
                 version.clone(),
             ));
-            // TODO: if we have seen `php-…` variants like `php-ipv6` or `php-zts`, should we warn or fail?
+            // any unsupported variants have caused an error above, and the others are provided by all runtimes
             root_package
                 .package
                 .require
@@ -320,7 +696,17 @@ pub(crate) fn ensure_runtime_requirement(
         }
     }
 
-    // TODO: generate conflict entries for php-zts, php-debug?
+    // none of our runtimes provide these, so this has no effect on resolution with our repositories
+    // it ensures that a custom platform repository cannot introduce a runtime that provides one of them
+    root_package
+        .package
+        .conflict
+        .get_or_insert(IndexMap::new()) // could be None
+        .extend(
+            UNSUPPORTED_RUNTIME_VARIANTS
+                .iter()
+                .map(|variant| ((*variant).to_string(), "*".to_string())),
+        );
 
     Ok(notices)
 }
@@ -1,10 +1,13 @@
 use crate::package_manager::composer::{
-    PlatformExtractorError, PlatformExtractorNotice, PlatformFinalizerError,
-    PlatformFinalizerNotice,
+    COMPOSER_CONFIG_ENV_VARS, ComposerSettings, ComposerSettingsError, PlatformExtractorError,
+    PlatformExtractorNotice, PlatformFinalizerError, PlatformFinalizerNotice,
+    is_composer_platform_package,
 };
 use crate::platform;
-use crate::platform::generator::{PlatformGeneratorError, PlatformJsonGeneratorInput};
-use ::composer::{ComposerLock, ComposerRootPackage};
+use crate::platform::automatic::AutomaticRequirement;
+use crate::platform::generator::PlatformGeneratorError;
+use crate::platform::libraries::LibraryRequirement;
+use ::composer::{ComposerHerokuExtra, ComposerLock, ComposerRootPackage, ComposerValidationError};
 use bullet_stream::style;
 use fs_err as fs;
 use indexmap::IndexMap;
@@ -17,11 +20,21 @@ use warned::Warned;
 pub(crate) struct ProjectLoader {
     composer_json_name: String,
     composer_lock_name: String,
+    /// Values of the environment variables in [`COMPOSER_CONFIG_ENV_VARS`] that are set
+    config_env_overrides: IndexMap<String, String>,
 }
 
 #[derive(Debug)]
 pub(crate) enum ProjectLoaderNotice {
     NameFromEnvVar(String, String),
+    /// The environment variable with the given name and value overrides the given Composer setting
+    ConfigFromEnvVar(String, String, String),
+    /// The given `COMPOSER_CACHE_DIR` value is not used, as the buildpack manages Composer's cache
+    CacheDirFromEnvVarIgnored(String),
+    /// The `composer.json` with the given file name has invalid package names
+    InvalidPackageNames(String, Vec<ComposerValidationError>),
+    /// The lock with the given file name is out of date with the `composer.json` with the given file name
+    StaleLock(String, String),
 }
 
 impl ProjectLoader {
@@ -29,6 +42,7 @@ impl ProjectLoader {
         Self {
             composer_json_name,
             composer_lock_name,
+            config_env_overrides: IndexMap::new(),
         }
     }
 
@@ -52,7 +66,30 @@ impl ProjectLoader {
                 .unwrap_or(&composer_json_name)
         );
 
-        Warned::new(Self::new(composer_json_name, composer_lock_name), notices)
+        // Composer settings like the vendor-dir can be overridden using environment variables
+        let config_env_overrides = COMPOSER_CONFIG_ENV_VARS
+            .iter()
+            .filter_map(|(env_var, setting)| {
+                let value = env.get_string_lossy(env_var)?;
+                notices.push(ProjectLoaderNotice::ConfigFromEnvVar(
+                    (*env_var).to_string(),
+                    value.clone(),
+                    (*setting).to_string(),
+                ));
+                Some(((*env_var).to_string(), value))
+            })
+            .collect();
+        if let Some(cache_dir) = env.get_string_lossy("COMPOSER_CACHE_DIR") {
+            notices.push(ProjectLoaderNotice::CacheDirFromEnvVarIgnored(cache_dir));
+        }
+
+        Warned::new(
+            Self {
+                config_env_overrides,
+                ..Self::new(composer_json_name, composer_lock_name)
+            },
+            notices,
+        )
     }
 
     pub(crate) fn detect(&self, project_dir: &Path) -> bool {
@@ -63,20 +100,45 @@ impl ProjectLoader {
         format!("No {} found.", style::value(&self.composer_json_name))
     }
 
-    pub(crate) fn load(&self, project_dir: &Path) -> Result<Project, ProjectLoadError> {
+    pub(crate) fn load(
+        &self,
+        project_dir: &Path,
+    ) -> Result<Warned<Project, ProjectLoaderNotice>, ProjectLoadError> {
         let composer_json_path = project_dir.join(&self.composer_json_name);
         let composer_lock_path = project_dir.join(&self.composer_lock_name);
 
         let composer_json = fs::read(composer_json_path)
             .map_err(|e| ProjectLoadError::ComposerJsonRead(self.composer_json_name.clone(), e))?;
 
+        // validate the document first, so that problems can be reported with their exact locations
+        let document = serde_json::from_slice(&composer_json)
+            .map_err(|e| ProjectLoadError::ComposerJsonParse(self.composer_json_name.clone(), e))?;
+        ::composer::validate_composer_json(&document).map_err(|errors| {
+            ProjectLoadError::ComposerJsonInvalid(self.composer_json_name.clone(), errors)
+        })?;
+        // invalid package names do not stop Composer from installing, so we only warn about them
+        let mut notices = Vec::new();
+        let name_problems = ::composer::check_package_names(&document);
+        if !name_problems.is_empty() {
+            notices.push(ProjectLoaderNotice::InvalidPackageNames(
+                self.composer_json_name.clone(),
+                name_problems,
+            ));
+        }
+
         let composer_json = serde_json::from_slice::<ComposerRootPackage>(&composer_json)
             .map_err(|e| ProjectLoadError::ComposerJsonParse(self.composer_json_name.clone(), e))?;
 
+        let composer_settings =
+            ComposerSettings::resolve(composer_json.config.as_ref(), &self.config_env_overrides)
+                .map_err(ProjectLoadError::ComposerSettings)?;
+
         let composer_lock = match fs::read(composer_lock_path) {
-            Ok(json) => Ok(Some(serde_json::from_slice(&json).map_err(|e| {
-                ProjectLoadError::ComposerLockParse(self.composer_lock_name.clone(), e)
-            })?)),
+            Ok(json) => Ok(Some(
+                serde_json::from_slice::<ComposerLock>(&json).map_err(|e| {
+                    ProjectLoadError::ComposerLockParse(self.composer_lock_name.clone(), e)
+                })?,
+            )),
             Err(err) => match err.kind() {
                 io::ErrorKind::NotFound => Ok(None), // lock does not have to exist
                 _ => Err(err),
@@ -84,18 +146,40 @@ impl ProjectLoader {
         }
         .map_err(|e| ProjectLoadError::ComposerLockRead(self.composer_lock_name.clone(), e))?;
 
-        if composer_json.package.require.is_some() && composer_lock.is_none() {
-            // lock does have to exist after all if there are requirements in composer.json
+        // Composer installs from an outdated lock with a warning, so we do the same
+        if let Some(lock) = &composer_lock
+            && composer_json.content_hash().as_ref() != Some(&lock.content_hash)
+        {
+            notices.push(ProjectLoaderNotice::StaleLock(
+                self.composer_json_name.clone(),
+                self.composer_lock_name.clone(),
+            ));
+        }
+
+        if composer_lock.is_none()
+            && composer_json
+                .package
+                .require
+                .iter()
+                .flatten()
+                .any(|(name, _)| !is_composer_platform_package(name))
+        {
+            // lock does have to exist after all if there are userland requirements in composer.json
+            // platform requirements alone are fine, as nothing has to be resolved for them that a lock would pin
             Err(ProjectLoadError::ComposerLockMissing(
                 self.composer_json_name.clone(),
                 self.composer_lock_name.clone(),
             ))
         } else {
-            Ok(Project::new(
-                self.composer_json_name.clone(),
-                self.composer_lock_name.clone(),
-                composer_json,
-                composer_lock,
+            Ok(Warned::new(
+                Project::new(
+                    self.composer_json_name.clone(),
+                    self.composer_lock_name.clone(),
+                    composer_json,
+                    composer_lock,
+                    composer_settings,
+                ),
+                notices,
             ))
         }
     }
@@ -106,9 +190,11 @@ impl ProjectLoader {
 pub(crate) enum ProjectLoadError {
     ComposerJsonRead(String, io::Error),
     ComposerJsonParse(String, serde_json::Error),
+    ComposerJsonInvalid(String, Vec<ComposerValidationError>),
     ComposerLockRead(String, io::Error),
     ComposerLockParse(String, serde_json::Error),
     ComposerLockMissing(String, String),
+    ComposerSettings(ComposerSettingsError),
 }
 
 #[derive(Debug)]
@@ -121,19 +207,25 @@ pub(crate) enum PlatformJsonError {
 #[derive(Debug)]
 pub(crate) enum PlatformJsonNotice {
     Extractor(PlatformExtractorNotice),
+    AutomaticRequirement(AutomaticRequirement),
     Finalizer(PlatformFinalizerNotice),
 }
 
+#[derive(Debug)]
+pub(crate) enum HerokuExtraError {
+    /// `extra.heroku` in the `composer.json` with the given file name is invalid
+    Parse(String, serde_json::Error),
+}
+
 #[derive(Default)]
 #[allow(clippy::struct_field_names)]
 pub(crate) struct Project {
-    #[allow(dead_code)]
     composer_json_name: String,
     #[allow(dead_code)]
     composer_lock_name: String,
-    #[allow(dead_code)]
     composer_json: ComposerRootPackage,
     composer_lock: Option<ComposerLock>,
+    composer_settings: ComposerSettings,
 }
 
 impl Project {
@@ -142,12 +234,52 @@ impl Project {
         composer_lock_name: String,
         composer_json: ComposerRootPackage,
         composer_lock: Option<ComposerLock>,
+        composer_settings: ComposerSettings,
     ) -> Self {
         Self {
             composer_json_name,
             composer_lock_name,
             composer_json,
             composer_lock,
+            composer_settings,
+        }
+    }
+
+    /// The Heroku specific settings from `extra.heroku` in `composer.json`.
+    pub(crate) fn heroku_extra(&self) -> Result<ComposerHerokuExtra, HerokuExtraError> {
+        self.composer_json
+            .heroku_extra()
+            .map_err(|e| HerokuExtraError::Parse(self.composer_json_name.clone(), e))
+    }
+
+    /// The Composer settings in effect for the project, e.g. its vendor-dir.
+    pub(crate) fn composer_settings(&self) -> &ComposerSettings {
+        &self.composer_settings
+    }
+
+    /// The project's lock file, if it has one.
+    pub(crate) fn composer_lock(&self) -> Option<&ComposerLock> {
+        self.composer_lock.as_ref()
+    }
+
+    /// The project's requirements for libraries such as `lib-icu`, which are not installed, but checked after installation.
+    pub(crate) fn library_requirements(&self) -> Vec<LibraryRequirement> {
+        match &self.composer_lock {
+            Some(lock) => platform::libraries::library_requirements(lock),
+            // without a lock, the root package's requirements are the only ones there are
+            None => self
+                .composer_json
+                .package
+                .require
+                .iter()
+                .flatten()
+                .filter(|(name, _)| name.starts_with("lib-"))
+                .map(|(name, constraint)| LibraryRequirement {
+                    name: name.clone(),
+                    constraint: constraint.clone(),
+                    required_by: None,
+                })
+                .collect(),
         }
     }
 
@@ -155,22 +287,53 @@ impl Project {
         &self,
         stack: &str,
         platform_repositories: &[Url],
+        automatic_require: &[AutomaticRequirement],
+        automatic_replace: &IndexMap<String, String>,
+        apply_php_platform_override: bool,
         dev: bool,
     ) -> Result<Warned<ComposerRootPackage, PlatformJsonNotice>, PlatformJsonError> {
         let mut extractor_notices = Vec::new();
-        let generator_input = match &self.composer_lock {
-            Some(l) => crate::package_manager::composer::extract_from_lock(l)
-                .map_err(PlatformJsonError::Extractor)?,
-            None => Warned::from(PlatformJsonGeneratorInput {
-                additional_require: Some(IndexMap::from([(
-                    "heroku-sys/composer".to_string(),
-                    "*".to_string(),
-                )])),
-                ..Default::default()
-            }),
+        let mut generator_input = match &self.composer_lock {
+            Some(l) => {
+                crate::package_manager::composer::extract_from_lock(l, apply_php_platform_override)
+                    .map_err(PlatformJsonError::Extractor)?
+            }
+            None => Warned::from(
+                crate::package_manager::composer::extract_from_composer_json(&self.composer_json),
+            ),
         }
         .unwrap(&mut extractor_notices); // Warned::unwrap does not panic :)
 
+        // automatic requirements must not override any the project has itself
+        let mut automatic_notices = Vec::new();
+        for requirement in automatic_require {
+            let name = requirement
+                .name
+                .strip_prefix("heroku-sys/")
+                .unwrap_or(&requirement.name);
+            if generator_input.platform_require.contains_key(name)
+                || generator_input
+                    .platform_require
+                    .contains_key(&requirement.name)
+            {
+                continue;
+            }
+            generator_input
+                .additional_require
+                .get_or_insert_default()
+                .insert(requirement.name.clone(), requirement.constraint.clone());
+            automatic_notices.push(PlatformJsonNotice::AutomaticRequirement(
+                requirement.clone(),
+            ));
+        }
+
+        if !automatic_replace.is_empty() {
+            generator_input
+                .additional_replace
+                .get_or_insert_default()
+                .extend(automatic_replace.clone());
+        }
+
         let mut ret = platform::generator::generate_platform_json(
             &generator_input,
             stack,
@@ -194,6 +357,7 @@ impl Project {
             extractor_notices
                 .into_iter()
                 .map(PlatformJsonNotice::Extractor)
+                .chain(automatic_notices)
                 .chain(
                     finalizer_notices
                         .into_iter()
@@ -205,7 +369,6 @@ impl Project {
     #[allow(dead_code)]
     pub(crate) fn validate(&self) -> Result<(), String> {
         // TODO: call "composer validate"?
-        //       ^ yes, also for lockfile freshness check
         //       ^ also as a fallback validation for when we have a Category::Data error
 
         // FIXME: we have to fail (or warn?) if heroku/heroku-buildpack-php is a dependency
@@ -1,8 +1,13 @@
+pub(crate) mod automatic;
 pub(crate) mod generator;
+pub(crate) mod libraries;
+pub(crate) mod preflight;
+pub(crate) mod repository;
 
 use crate::PhpBuildpack;
 use crate::bootstrap;
 use crate::platform::generator::PlatformGeneratorError;
+use crate::web::WebServer;
 use composer::ComposerRootPackage;
 use indexmap::IndexMap;
 use libcnb::build::BuildContext;
@@ -144,11 +149,24 @@ pub(crate) enum WebserversJsonError {
 pub(crate) fn webservers_json(
     stack: &str,
     platform_repositories: &[Url],
+    engines: &IndexMap<WebServer, String>,
 ) -> Result<ComposerRootPackage, WebserversJsonError> {
+    let engine_constraint = |web_server| {
+        engines
+            .get(&web_server)
+            .cloned()
+            .unwrap_or_else(|| "*".to_string())
+    };
     let webservers_generator_input = generator::PlatformJsonGeneratorInput {
         additional_require: Some(IndexMap::from([
-            ("heroku-sys/apache".to_string(), "*".to_string()),
-            ("heroku-sys/nginx".to_string(), "*".to_string()),
+            (
+                "heroku-sys/apache".to_string(),
+                engine_constraint(WebServer::Apache),
+            ),
+            (
+                "heroku-sys/nginx".to_string(),
+                engine_constraint(WebServer::Nginx),
+            ),
             // this package contains heroku-php-apache2 and heroku-php-nginx, plus runtime configs
             ("heroku-sys/boot-scripts".to_string(), "^1.0.0".to_string()),
         ])),
@@ -163,9 +181,7 @@ pub(crate) fn webservers_json(
     .map_err(WebserversJsonError::PlatformGenerator)?;
 
     //
-    let mut config_with_bin_dir = webservers_json.config.unwrap_or_default();
-    config_with_bin_dir.insert("bin-dir".to_string(), "bin".into());
-    webservers_json.config = Some(config_with_bin_dir);
+    webservers_json.config.get_or_insert_default().bin_dir = Some("bin".to_string());
 
     Ok(webservers_json)
 }
@@ -0,0 +1,49 @@
+use crate::utils::is_truthy;
+use libcnb::Env;
+
+/// A platform requirement that is added because of the build environment, rather than because
+/// the project asked for it, e.g. the extension for an APM service the app is configured for.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct AutomaticRequirement {
+    /// The package name, with the `heroku-sys/` prefix
+    pub(crate) name: String,
+    pub(crate) constraint: String,
+    /// Why the requirement was added, e.g. "`NEW_RELIC_LICENSE_KEY` is set"
+    pub(crate) reason: String,
+    /// The name of the environment variable that disables this requirement, if any
+    pub(crate) opt_out: Option<String>,
+}
+
+/// The environment variable that, if set to a truthy value, disables the New Relic extension.
+pub(crate) const NEWRELIC_OPT_OUT_ENV_VAR: &str = "HEROKU_PHP_SKIP_NEWRELIC";
+
+/// Determines the [`AutomaticRequirement`s](AutomaticRequirement) for extensions of services that
+/// are configured in the given build [`Env`], like `ext-newrelic` if `NEW_RELIC_LICENSE_KEY` is set.
+///
+/// Requirements whose opt-out environment variable is set to a truthy value are not returned.
+pub(crate) fn automatic_requirements(env: &Env) -> Vec<AutomaticRequirement> {
+    let mut requirements = vec![];
+
+    if env
+        .get_string_lossy("NEW_RELIC_LICENSE_KEY")
+        .is_some_and(|key| !key.is_empty())
+    {
+        requirements.push(AutomaticRequirement {
+            name: "heroku-sys/ext-newrelic".to_string(),
+            constraint: "*".to_string(),
+            reason: "'NEW_RELIC_LICENSE_KEY' is set".to_string(),
+            opt_out: Some(NEWRELIC_OPT_OUT_ENV_VAR.to_string()),
+        });
+    }
+
+    requirements
+        .into_iter()
+        .filter(|requirement| {
+            !requirement
+                .opt_out
+                .as_ref()
+                .and_then(|name| env.get_string_lossy(name))
+                .is_some_and(is_truthy)
+        })
+        .collect()
+}
@@ -1,12 +1,12 @@
 use crate::package_manager;
-use crate::utils::regex;
+use crate::utils::{is_truthy, regex};
 use chrono::offset::Utc;
 use composer::{
-    ComposerBasePackage, ComposerLock, ComposerPackage, ComposerRepositories, ComposerRepository,
-    ComposerRepositoryFilters, ComposerRootPackage, ComposerStability,
+    ComposerBasePackage, ComposerConfig, ComposerConfigAllowPlugins, ComposerConfigDiscardChanges,
+    ComposerLock, ComposerLockAlias, ComposerPackage, ComposerRepositories, ComposerRepository,
+    ComposerRepositoryFilters, ComposerRootPackage, ComposerStability, ComposerVersion,
 };
 use indexmap::IndexMap;
-use serde_json::json;
 use std::string::ToString;
 use url::Url;
 
@@ -35,7 +35,7 @@ fn split_and_trim_list<'a>(list: &'a str, sep: &'a str) -> impl Iterator<Item =
 ///
 /// These query args, if present, are not removed from the URL written to the [`ComposerRepository`]
 /// to ensure that a possible signature included in the URL string remains valid.
-fn composer_repository_from_repository_url(
+pub(crate) fn composer_repository_from_repository_url(
     url: Url,
 ) -> Result<ComposerRepository, ComposerRepositoryFromRepositoryUrlError> {
     const CANONICAL_QUERY_ARG_NAME: &str = "composer-repository-canonical";
@@ -49,10 +49,7 @@ fn composer_repository_from_repository_url(
         let v = v.as_ref();
         match k {
             CANONICAL_QUERY_ARG_NAME => {
-                canonical = Some(matches!(
-                    v.trim().to_ascii_lowercase().as_ref(),
-                    "1" | "true" | "on" | "yes"
-                ));
+                canonical = Some(is_truthy(v));
             }
             ONLY_QUERY_ARG_NAME | EXCLUDE_QUERY_ARG_NAME => {
                 if filters.is_some() {
@@ -110,12 +107,16 @@ pub(crate) struct PlatformJsonGeneratorInput {
     pub(crate) packages: Vec<ComposerPackage>,
     /// A list of packages from the source project's locked dev dependencies
     pub(crate) packages_dev: Vec<ComposerPackage>,
+    /// A list of inline aliases for packages from the source project's locked dependencies
+    pub(crate) aliases: Vec<ComposerLockAlias>,
     /// A list of additional requirements to be placed into the generated package's root requirements
     pub(crate) additional_require: Option<IndexMap<String, String>>,
     /// A list of additional requirements to be placed into the generated package's root dev requirements
     pub(crate) additional_require_dev: Option<IndexMap<String, String>>,
     /// Additional [`ComposerRepository`] entries to be placed into the generated package
     pub(crate) additional_repositories: Option<Vec<ComposerRepository>>,
+    /// Packages that the generated package replaces, so that they are not installed even if required
+    pub(crate) additional_replace: Option<IndexMap<String, String>>,
 }
 impl From<&ComposerLock> for PlatformJsonGeneratorInput {
     fn from(lock: &ComposerLock) -> Self {
@@ -126,13 +127,35 @@ impl From<&ComposerLock> for PlatformJsonGeneratorInput {
             platform_require_dev: (*lock.platform_dev).clone(),
             packages: lock.packages.clone(),
             packages_dev: lock.packages_dev.clone(),
+            aliases: lock.aliases.clone(),
             additional_require: None,
             additional_require_dev: None,
             additional_repositories: None,
+            additional_replace: None,
         }
     }
 }
 
+/// Returns the version constraint with which the given [`ComposerPackage`] is to be required.
+///
+/// If there is an inline alias for the package's version in the given list of [`ComposerLockAlias`]
+/// entries (e.g. because the source project requires `dev-main as 2.0.x-dev`), the same inline
+/// alias is used, so that the package is also available under its aliased version.
+fn require_version_with_alias(package: &ComposerPackage, aliases: &[ComposerLockAlias]) -> String {
+    let version = package.version.parse::<ComposerVersion>().ok();
+    aliases
+        .iter()
+        .find(|alias| {
+            alias.package == package.name
+                && version.is_some()
+                && alias.version.parse::<ComposerVersion>().ok() == version
+        })
+        .map_or_else(
+            || package.version.clone(),
+            |alias| format!("{} as {}", package.version, alias.alias),
+        )
+}
+
 fn stack_provide_from_stack_name(stack: &str) -> Result<(String, String), PlatformGeneratorError> {
     // from the given stack string like "heroku-99", make a ("heroku-sys/heroku", "99.2023.04.05") tuple for "provide" later
     let stack_captures = regex!(r"^(?P<stackname>[^-]+)(?:-(?P<stackversion>[0-9]+))?$")
@@ -229,6 +252,7 @@ pub(crate) fn generate_platform_json(
     // ^ with name and version copied
     // ^ with require, replace, provide and conflict entries that reference a platform package, again with a "heroku-sys/" prefix
     // ^ these metapackages are inserted together as a "package" type repository, and a require(-dev) entry is written for each
+    // ^ that require(-dev) entry uses the same inline alias as the source project, if any (think "dev-main as 2.0.x-dev")
     //
     // regardless of dev install or not, we process all platform-dev and packages-dev packages so the caller can tell later if there is no version requirement in all of require, but in require-dev
     // ^ this might be desired to ensure folks get the same PHP version etc as locally/CI
@@ -268,7 +292,10 @@ pub(crate) fn generate_platform_json(
                 metapackages
                     // ... and insert a require for each of them
                     .inspect(|package| {
-                        requires.insert(package.name.clone(), package.version.clone());
+                        requires.insert(
+                            package.name.clone(),
+                            require_version_with_alias(package, &input.aliases),
+                        );
                     })
                     .collect(),
             );
@@ -281,21 +308,20 @@ pub(crate) fn generate_platform_json(
     require_dev.extend(input.additional_require_dev.clone().unwrap_or_default());
 
     Ok(ComposerRootPackage {
-        config: json!({
-            "cache-files-ttl": 0,
-            "discard-changes": true,
-            "allow-plugins": {
-                "heroku/installer-plugin": true,
-            }
-        })
-        .as_object()
-        .map(ToOwned::to_owned)
-        .map(IndexMap::from_iter),
+        config: Some(ComposerConfig {
+            allow_plugins: Some(ComposerConfigAllowPlugins::List(IndexMap::from([(
+                "heroku/installer-plugin".to_string(),
+                true,
+            )]))),
+            cache_files_ttl: Some(0),
+            discard_changes: Some(ComposerConfigDiscardChanges::Boolean(true)),
+            ..Default::default()
+        }),
         minimum_stability: Some(input.minimum_stability.clone()),
         prefer_stable: Some(input.prefer_stable),
         package: ComposerBasePackage {
             provide: Some(IndexMap::from([stack_provide])),
-            replace: None, // TODO: blackfire
+            replace: input.additional_replace.clone(),
             repositories: Some(ComposerRepositories::from(repositories)),
             require: (!require.is_empty()).then_some(require),
             require_dev: (!require_dev.is_empty()).then_some(require_dev),
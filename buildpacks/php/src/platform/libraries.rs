@@ -0,0 +1,172 @@
+use crate::utils::regex;
+use composer::{ComposerLock, ComposerVersion, ComposerVersionConstraint};
+use indexmap::IndexMap;
+use libcnb::Env;
+use std::process::{Command, Output};
+use warned::Warned;
+
+/// A requirement for a library (a `lib-…` platform package) that PHP or one of its extensions is linked against.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct LibraryRequirement {
+    /// The package name, e.g. `lib-icu`
+    pub(crate) name: String,
+    pub(crate) constraint: String,
+    /// The name of the requiring package, or `None` for the root package
+    pub(crate) required_by: Option<String>,
+}
+
+#[derive(Debug)]
+pub(crate) enum LibraryCheckError {
+    PhpInvocation(std::io::Error),
+    PhpOutput(Output),
+    UnsatisfiedRequirements {
+        /// The unsatisfied requirements, with the installed version of each library
+        requirements: Vec<(LibraryRequirement, String)>,
+    },
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum LibraryCheckNotice {
+    /// The version of the required library could not be determined, e.g. because the extension
+    /// using it is not installed, or because it is not one we know how to check
+    UnknownLibraryVersion(LibraryRequirement),
+    /// The requirement is not a valid version constraint
+    InvalidConstraint(LibraryRequirement),
+}
+
+/// Collects all `lib-…` requirements from the given [`ComposerLock`].
+///
+/// These are the requirements in the root package's `platform`, and those in any of the (non-dev) packages.
+/// Such requirements are not part of the platform package installation, since libraries come with the stack.
+pub(crate) fn library_requirements(lock: &ComposerLock) -> Vec<LibraryRequirement> {
+    let is_library = |name: &&String| name.starts_with("lib-");
+
+    lock.platform
+        .iter()
+        .filter(|(name, _)| is_library(name))
+        .map(|(name, constraint)| LibraryRequirement {
+            name: name.clone(),
+            constraint: constraint.clone(),
+            required_by: None,
+        })
+        .chain(lock.packages.iter().flat_map(|package| {
+            package
+                .package
+                .require
+                .iter()
+                .flatten()
+                .filter(|(name, _)| is_library(name))
+                .map(|(name, constraint)| LibraryRequirement {
+                    name: name.clone(),
+                    constraint: constraint.clone(),
+                    required_by: Some(package.name.clone()),
+                })
+        }))
+        .collect()
+}
+
+/// The PHP code used to determine library versions.
+///
+/// This covers the most commonly used subset of the libraries Composer's platform repository knows.
+pub(crate) const LIBRARY_VERSIONS_SCRIPT: &str = r"
+$v = [];
+if (defined('INTL_ICU_VERSION')) $v['lib-icu'] = INTL_ICU_VERSION;
+if (defined('OPENSSL_VERSION_TEXT')) $v['lib-openssl'] = OPENSSL_VERSION_TEXT;
+if (function_exists('curl_version')) $v['lib-curl'] = curl_version()['version'];
+if (defined('LIBXML_DOTTED_VERSION')) $v['lib-libxml'] = LIBXML_DOTTED_VERSION;
+if (defined('LIBXSLT_DOTTED_VERSION')) $v['lib-xsl'] = LIBXSLT_DOTTED_VERSION;
+if (defined('PCRE_VERSION')) $v['lib-pcre'] = PCRE_VERSION;
+if (defined('ZLIB_VERSION')) $v['lib-zlib'] = ZLIB_VERSION;
+if (defined('ICONV_VERSION')) $v['lib-iconv'] = ICONV_VERSION;
+if (defined('GMP_VERSION')) $v['lib-gmp'] = GMP_VERSION;
+if (defined('SODIUM_LIBRARY_VERSION')) $v['lib-libsodium'] = SODIUM_LIBRARY_VERSION;
+if (defined('MB_ONIGURUMA_VERSION')) $v['lib-mbstring-oniguruma'] = MB_ONIGURUMA_VERSION;
+if (class_exists('ZipArchive') && defined('ZipArchive::LIBZIP_VERSION')) $v['lib-zip-libzip'] = ZipArchive::LIBZIP_VERSION;
+echo json_encode((object)$v);
+";
+
+/// Asks the PHP binary found in the given [`Env`] for the versions of the libraries it (or its extensions) use.
+///
+/// Versions are reduced using [`library_version`], so e.g. `OpenSSL 3.0.13 30 Jan 2024` becomes `3.0.13`.
+pub(crate) fn installed_library_versions(
+    command_env: &Env,
+) -> Result<IndexMap<String, ComposerVersion>, LibraryCheckError> {
+    let output = Command::new("php")
+        .args(["-r", LIBRARY_VERSIONS_SCRIPT])
+        .envs(command_env)
+        .output()
+        .map_err(LibraryCheckError::PhpInvocation)?;
+
+    if !output.status.success() {
+        return Err(LibraryCheckError::PhpOutput(output));
+    }
+
+    let Ok(versions) = serde_json::from_slice::<IndexMap<String, String>>(&output.stdout) else {
+        return Err(LibraryCheckError::PhpOutput(output));
+    };
+
+    Ok(versions
+        .into_iter()
+        .filter_map(|(name, raw)| library_version(&name, &raw).map(|version| (name, version)))
+        .collect())
+}
+
+/// Reduces the given raw version of the library with the given name to its leading dotted numeric part.
+///
+/// Letter suffixes of OpenSSL versions before 3.0 (like the `w` in `1.1.1w`) become a fourth
+/// number (`1.1.1.23`), just like Composer's `PlatformRepository` does.
+pub(crate) fn library_version(name: &str, raw: &str) -> Option<ComposerVersion> {
+    let captures = regex!(r"(\d+(?:\.\d+)*)([a-z]{0,2})").captures(raw)?;
+    let mut version = captures[1].to_string();
+    let letters = &captures[2];
+    if name == "lib-openssl"
+        && !letters.is_empty()
+        && version
+            .parse::<ComposerVersion>()
+            .is_ok_and(|v| v.major().is_some_and(|major| major < 3))
+    {
+        // same as Composer's Version::convertAlphaVersionToIntVersion(), so "a" is 1, "z" is 26, "za" is 27
+        let number = letters
+            .bytes()
+            .map(|b| u32::from(b - b'a' + 1))
+            .sum::<u32>();
+        version = format!("{version}.{number}");
+    }
+    version.parse().ok()
+}
+
+/// Checks the given [`LibraryRequirement`s](LibraryRequirement) against the given installed library versions.
+///
+/// Requirements for libraries whose version is unknown, and requirements that are not valid
+/// constraints, are not checked, but reported as notices.
+pub(crate) fn check_library_requirements(
+    requirements: &[LibraryRequirement],
+    installed: &IndexMap<String, ComposerVersion>,
+) -> Result<Warned<(), LibraryCheckNotice>, LibraryCheckError> {
+    let mut notices = vec![];
+    let mut unsatisfied = vec![];
+
+    for requirement in requirements {
+        let Some(version) = installed.get(&requirement.name) else {
+            notices.push(LibraryCheckNotice::UnknownLibraryVersion(
+                requirement.clone(),
+            ));
+            continue;
+        };
+        let Ok(constraint) = requirement.constraint.parse::<ComposerVersionConstraint>() else {
+            notices.push(LibraryCheckNotice::InvalidConstraint(requirement.clone()));
+            continue;
+        };
+        if !constraint.matches(version) {
+            unsatisfied.push((requirement.clone(), version.to_string()));
+        }
+    }
+
+    if unsatisfied.is_empty() {
+        Ok(Warned::new((), notices))
+    } else {
+        Err(LibraryCheckError::UnsatisfiedRequirements {
+            requirements: unsatisfied,
+        })
+    }
+}
@@ -0,0 +1,92 @@
+use crate::platform::repository::AvailablePlatformPackages;
+use composer::{ComposerRepository, ComposerRootPackage, ComposerVersionConstraint};
+
+#[derive(Debug)]
+pub(crate) enum PreflightCheckError {
+    UnsatisfiableRuntimeRequirement {
+        stack: String,
+        arch: String,
+        /// The runtime requirements, with the name of the requiring package (or `None` for the root package)
+        requirements: Vec<(Option<String>, String)>,
+        available_versions: Vec<String>,
+    },
+}
+
+/// Collects all requirements for `heroku-sys/php` from the given platform [`ComposerRootPackage`].
+///
+/// These are the requirement in the root package itself, and those in any of the metapackages
+/// (generated from the project's locked dependencies) that are required by the root package.
+fn runtime_requirements(platform_json: &ComposerRootPackage) -> Vec<(Option<String>, String)> {
+    const RUNTIME_NAME: &str = "heroku-sys/php";
+
+    let root_require = platform_json.package.require.clone().unwrap_or_default();
+
+    root_require
+        .get(RUNTIME_NAME)
+        .map(|constraint| (None, constraint.clone()))
+        .into_iter()
+        .chain(
+            platform_json
+                .package
+                .repositories
+                .iter()
+                .flat_map(|repositories| repositories.iter())
+                .filter_map(|repository| match repository {
+                    ComposerRepository::Package { package, .. } => Some(package),
+                    _ => None,
+                })
+                .flatten()
+                // only packages that will actually get installed are relevant (so no require-dev)
+                .filter(|package| root_require.contains_key(&package.name))
+                .filter_map(|package| {
+                    package
+                        .package
+                        .require
+                        .as_ref()
+                        .and_then(|require| require.get(RUNTIME_NAME))
+                        .map(|constraint| (Some(package.name.clone()), constraint.clone()))
+                }),
+        )
+        .collect()
+}
+
+/// Checks whether at least one of the runtime versions in the given [`AvailablePlatformPackages`]
+/// satisfies all runtime requirements in the given platform [`ComposerRootPackage`].
+///
+/// This explains a failed installation, as the repository metadata is only fetched for that.
+///
+/// The check is skipped if there are no available runtime versions (e.g. because only custom
+/// platform repositories without runtimes are configured). Requirements that are not valid version
+/// constraints are left out of the check (and the error); the installation will report those.
+pub(crate) fn check_runtime_requirements(
+    platform_json: &ComposerRootPackage,
+    available: &AvailablePlatformPackages,
+    stack: &str,
+    arch: &str,
+) -> Result<(), PreflightCheckError> {
+    let (requirements, constraints): (Vec<_>, Vec<_>) = runtime_requirements(platform_json)
+        .into_iter()
+        .filter_map(|requirement| {
+            let constraint = requirement.1.parse::<ComposerVersionConstraint>().ok()?;
+            Some((requirement, constraint))
+        })
+        .unzip();
+    if available.runtimes.is_empty() || requirements.is_empty() {
+        return Ok(());
+    }
+
+    if available.runtimes.iter().any(|version| {
+        constraints
+            .iter()
+            .all(|constraint| constraint.matches(version))
+    }) {
+        Ok(())
+    } else {
+        Err(PreflightCheckError::UnsatisfiableRuntimeRequirement {
+            stack: stack.to_string(),
+            arch: arch.to_string(),
+            requirements,
+            available_versions: available.runtimes.iter().map(ToString::to_string).collect(),
+        })
+    }
+}
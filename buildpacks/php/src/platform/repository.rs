@@ -0,0 +1,189 @@
+use crate::PhpBuildpackNotice;
+use crate::errors::notices;
+use crate::platform::generator::{
+    ComposerRepositoryFromRepositoryUrlError, composer_repository_from_repository_url,
+};
+use crate::utils::{DownloadUnpackError, download_with_retry};
+use composer::{
+    ComposerPackage, ComposerRepository, ComposerRepositoryFilters, ComposerRepositoryMetadata,
+    ComposerVersion,
+};
+use fs_err as fs;
+use indexmap::{IndexMap, IndexSet};
+use std::cell::OnceCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use url::Url;
+
+#[derive(Debug)]
+pub(crate) enum PlatformRepositoryMetadataError {
+    FromRepositoryUrl(Url, ComposerRepositoryFromRepositoryUrlError),
+    Read(Url, io::Error),
+    Download(Url, DownloadUnpackError),
+    Parse(Url, serde_json::Error),
+}
+
+/// Fetches and parses the `packages.json` of the platform repository at the given [`Url`].
+///
+/// Besides `http(s)://` URLs, `file://` URLs are supported, just like in Composer.
+pub(crate) fn fetch_repository_metadata(
+    url: &Url,
+) -> Result<ComposerRepositoryMetadata, PlatformRepositoryMetadataError> {
+    match url.to_file_path() {
+        Ok(path) if url.scheme() == "file" => {
+            let json = fs::read(path)
+                .map_err(|e| PlatformRepositoryMetadataError::Read(url.clone(), e))?;
+            serde_json::from_slice(&json)
+        }
+        _ => serde_json::from_reader(
+            download_with_retry(url.as_str())
+                .map_err(|e| PlatformRepositoryMetadataError::Download(url.clone(), e))?,
+        ),
+    }
+    .map_err(|e| PlatformRepositoryMetadataError::Parse(url.clone(), e))
+}
+
+/// A summary of the runtimes and extensions available in a list of platform repositories.
+///
+/// Package names are stored without their `heroku-sys/` prefix, and versions are sorted.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AvailablePlatformPackages {
+    /// The available versions of the `php` package
+    pub(crate) runtimes: IndexSet<ComposerVersion>,
+    /// The names of all available extensions, with all their available versions
+    pub(crate) extensions: IndexMap<String, IndexSet<ComposerVersion>>,
+}
+
+impl AvailablePlatformPackages {
+    fn insert(&mut self, package: &ComposerPackage) {
+        let name = package
+            .name
+            .strip_prefix("heroku-sys/")
+            .unwrap_or(&package.name);
+        // a package with an invalid version could never be installed anyway
+        let Ok(version) = package.version.parse::<ComposerVersion>() else {
+            return;
+        };
+        match package.package.kind.as_deref() {
+            Some("heroku-sys-php") if name == "php" => {
+                self.runtimes.insert(version);
+            }
+            Some("heroku-sys-php-extension") => {
+                self.extensions
+                    .entry(name.to_string())
+                    .or_default()
+                    .insert(version);
+            }
+            _ => (),
+        }
+    }
+
+    fn sort(&mut self) {
+        self.runtimes.sort();
+        self.extensions.values_mut().for_each(IndexSet::sort);
+    }
+}
+
+impl fmt::Display for AvailablePlatformPackages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut extension_names = self.extensions.keys().cloned().collect::<Vec<_>>();
+        extension_names.sort();
+        writeln!(
+            f,
+            "PHP: {}",
+            self.runtimes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        write!(f, "Extensions: {}", extension_names.join(", "))
+    }
+}
+
+/// Fetches the metadata of all given platform repository [`Url`s](Url), and summarizes the
+/// runtimes and extensions available in them into an [`AvailablePlatformPackages`].
+///
+/// The given list is expected in ascending order of precedence, like for
+/// [`generate_platform_json`](crate::platform::generator::generate_platform_json), and the same
+/// URL query args for repository canonicity and package filters are honored.
+pub(crate) fn available_platform_packages(
+    platform_repositories: &[Url],
+) -> Result<AvailablePlatformPackages, PlatformRepositoryMetadataError> {
+    let mut available = AvailablePlatformPackages::default();
+    // names of packages found in a canonical repository, which prevents lookups in later repositories
+    let mut canonical_names = HashSet::new();
+
+    for url in platform_repositories.iter().rev() {
+        let (canonical, filters) = match composer_repository_from_repository_url(url.clone())
+            .map_err(|e| PlatformRepositoryMetadataError::FromRepositoryUrl(url.clone(), e))?
+        {
+            ComposerRepository::Composer {
+                canonical, filters, ..
+            } => (canonical.unwrap_or(true), filters),
+            _ => (true, None),
+        };
+
+        let metadata = fetch_repository_metadata(url)?;
+        let mut seen_names = HashSet::new();
+        for package in metadata.packages.iter().filter(|package| {
+            !canonical_names.contains(&package.name)
+                && match &filters {
+                    Some(ComposerRepositoryFilters::Only(names)) => names.contains(&package.name),
+                    Some(ComposerRepositoryFilters::Exclude(names)) => {
+                        !names.contains(&package.name)
+                    }
+                    None => true,
+                }
+        }) {
+            seen_names.insert(package.name.clone());
+            available.insert(package);
+        }
+
+        if canonical {
+            canonical_names.extend(seen_names);
+        }
+    }
+
+    available.sort();
+    Ok(available)
+}
+
+/// The [`AvailablePlatformPackages`] of a list of platform repository [`Url`s](Url), which are only
+/// fetched on first use.
+///
+/// Downloading the metadata of all repositories takes time, and it is only needed to explain a
+/// failed installation, or for verbose output, so most builds never do it.
+pub(crate) struct LazyAvailablePlatformPackages<'a> {
+    platform_repositories: &'a [Url],
+    available: OnceCell<Option<AvailablePlatformPackages>>,
+}
+
+impl<'a> LazyAvailablePlatformPackages<'a> {
+    /// See [`available_platform_packages`] for the expected order of the given list.
+    pub(crate) fn new(platform_repositories: &'a [Url]) -> Self {
+        Self {
+            platform_repositories,
+            available: OnceCell::new(),
+        }
+    }
+
+    /// The [`AvailablePlatformPackages`], fetched on the first call.
+    ///
+    /// None of the uses are essential, so if the metadata cannot be loaded, a notice is logged
+    /// (once), and `None` is returned.
+    pub(crate) fn get(&self) -> Option<&AvailablePlatformPackages> {
+        self.available
+            .get_or_init(
+                || match available_platform_packages(self.platform_repositories) {
+                    Ok(available) => Some(available),
+                    Err(e) => {
+                        notices::log(PhpBuildpackNotice::PlatformRepositoryMetadata(e));
+                        None
+                    }
+                },
+            )
+            .as_ref()
+    }
+}
@@ -0,0 +1,86 @@
+use composer::{ComposerInstalledRepository, ComposerLock};
+use libcnb::data::sbom::SbomFormat;
+use libcnb::sbom::Sbom;
+use serde::Serialize;
+use serde_json::json;
+
+/// A component (a package) listed in an SBOM.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct SbomComponent {
+    /// The component type, e.g. `library`
+    #[serde(rename = "type")]
+    pub(crate) kind: &'static str,
+    pub(crate) name: String,
+    pub(crate) version: String,
+    /// The package URL, see <https://github.com/package-url/purl-spec>
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) purl: Option<String>,
+}
+
+/// Builds an [`Sbom`] in `CycloneDX` JSON format (spec version 1.4), listing the given components.
+pub(crate) fn cyclonedx_sbom(components: &[SbomComponent]) -> Sbom {
+    Sbom::from_bytes(
+        SbomFormat::CycloneDxJson,
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.4",
+            "version": 1,
+            "components": components,
+        })
+        .to_string(),
+    )
+}
+
+/// Lists the platform packages (runtimes, extensions, programs) from the given
+/// [`ComposerInstalledRepository`] of the platform installation.
+///
+/// The `heroku-sys/` prefix is removed from names; other packages, like the installer plugin, are skipped.
+pub(crate) fn platform_components(installed: &ComposerInstalledRepository) -> Vec<SbomComponent> {
+    installed
+        .packages
+        .iter()
+        .map(|installed| &installed.package)
+        .filter_map(|package| {
+            let kind = package.package.kind.as_deref()?;
+            if !kind.starts_with("heroku-sys-") {
+                return None;
+            }
+            let name = package.name.strip_prefix("heroku-sys/")?.to_string();
+            Some(SbomComponent {
+                kind: if kind == "heroku-sys-php-extension" {
+                    "library"
+                } else {
+                    "application"
+                },
+                purl: Some(format!(
+                    "pkg:generic/{name}@{version}",
+                    version = purl_encode(&package.version)
+                )),
+                name,
+                version: package.version.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Lists the (non-dev) userland packages from the given [`ComposerLock`].
+pub(crate) fn composer_lock_components(lock: &ComposerLock) -> Vec<SbomComponent> {
+    lock.packages
+        .iter()
+        .map(|package| SbomComponent {
+            kind: "library",
+            name: package.name.clone(),
+            version: package.version.clone(),
+            purl: Some(format!(
+                "pkg:composer/{name}@{version}",
+                name = package.name,
+                version = purl_encode(&package.version)
+            )),
+        })
+        .collect()
+}
+
+/// Percent-encodes a version string for use in a package URL, e.g. `dev-feature/foo`.
+fn purl_encode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
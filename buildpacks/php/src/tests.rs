@@ -1 +1,26 @@
+mod audit;
+mod blackfire;
+mod layers;
+mod opcache;
+mod package_manager;
+mod php_project;
 mod platform;
+mod sbom;
+mod web;
+
+use composer::ComposerLock;
+use fs_err as fs;
+use serde_json::Value;
+
+/// An empty lock file, for tests that only need a few fields set.
+const EMPTY_LOCK_FIXTURE: &str = "tests/fixtures/empty.lock";
+
+/// A [`ComposerLock`] with the given fields, and all others from [`EMPTY_LOCK_FIXTURE`].
+fn lock_with(fields: Value) -> ComposerLock {
+    let mut lock = serde_json::from_slice::<Value>(&fs::read(EMPTY_LOCK_FIXTURE).unwrap()).unwrap();
+    let Value::Object(fields) = fields else {
+        panic!("fields must be an object");
+    };
+    lock.as_object_mut().unwrap().extend(fields);
+    serde_json::from_value(lock).unwrap()
+}
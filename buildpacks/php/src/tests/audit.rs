@@ -0,0 +1,167 @@
+use crate::audit::{Advisory, AdvisorySeverity, AuditError, AuditNotice, audit};
+use crate::tests::lock_with;
+use composer::ComposerLock;
+use fs_err as fs;
+use indoc::indoc;
+use serde_json::json;
+use std::path::Path;
+
+fn lock() -> ComposerLock {
+    lock_with(json!({
+        "packages": [
+            {"name": "acme/current", "version": "2.1.0"},
+            {"name": "acme/old", "version": "1.0.0", "abandoned": true},
+            {"name": "acme/replaced", "version": "v3.4.5", "abandoned": "acme/replacement"},
+            {"name": "acme/vulnerable", "version": "v5.4.3"},
+        ],
+        "packages-dev": [
+            {"name": "acme/dev-only", "version": "1.0.0", "abandoned": true},
+        ],
+    }))
+}
+
+fn write_advisory(dir: &Path, package: &str, id: &str, contents: &str) {
+    fs::create_dir_all(dir.join(package)).unwrap();
+    fs::write(dir.join(package).join(format!("{id}.yaml")), contents).unwrap();
+}
+
+#[test]
+fn abandoned() {
+    assert_eq!(
+        audit(&lock(), None, None).unwrap().warnings,
+        vec![
+            AuditNotice::AbandonedPackage("acme/old".to_string(), None),
+            AuditNotice::AbandonedPackage(
+                "acme/replaced".to_string(),
+                Some("acme/replacement".to_string())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn advisories() {
+    let advisories_dir = tempfile::tempdir().unwrap();
+    write_advisory(
+        advisories_dir.path(),
+        "acme/vulnerable",
+        "CVE-2024-0001",
+        indoc! {"
+            title:     Remote code execution in the request parser
+            link:      https://example.com/CVE-2024-0001
+            cve:       CVE-2024-0001
+            branches:
+                5.4.x:
+                    time:     2024-01-01 00:00:00
+                    versions: ['>=5.0.0', '<5.4.20']
+                6.x:
+                    time:     2024-01-01 00:00:00
+                    versions: ['>=6.0.0', '<6.2.6']
+            reference: composer://acme/vulnerable
+        "},
+    );
+    write_advisory(
+        advisories_dir.path(),
+        "acme/vulnerable",
+        "2023-01-01",
+        indoc! {"
+            title:     Already fixed issue
+            branches:
+                5.x:
+                    time:     2023-01-01 00:00:00
+                    versions: ['<5.4.0']
+            reference: composer://acme/vulnerable
+        "},
+    );
+    write_advisory(
+        advisories_dir.path(),
+        "acme/current",
+        "2024-02-02",
+        indoc! {"
+            title:     Minor information disclosure
+            branches:
+                2.x:
+                    time:     2024-02-02 00:00:00
+                    versions: ['<2.2.0']
+            reference: composer://acme/current
+            severity:  low
+        "},
+    );
+
+    let low = Advisory {
+        package: "acme/current".to_string(),
+        version: "2.1.0".to_string(),
+        title: "Minor information disclosure".to_string(),
+        cve: None,
+        link: None,
+        severity: Some(AdvisorySeverity::Low),
+    };
+    let unknown = Advisory {
+        package: "acme/vulnerable".to_string(),
+        version: "v5.4.3".to_string(),
+        title: "Remote code execution in the request parser".to_string(),
+        cve: Some("CVE-2024-0001".to_string()),
+        link: Some("https://example.com/CVE-2024-0001".to_string()),
+        severity: None,
+    };
+    assert_eq!(
+        unknown.to_string(),
+        "acme/vulnerable (v5.4.3): Remote code execution in the request parser [CVE-2024-0001] <https://example.com/CVE-2024-0001>"
+    );
+
+    // without a fail severity, all advisories are only reported
+    let warnings = audit(&lock(), Some(advisories_dir.path()), None)
+        .unwrap()
+        .warnings;
+    assert_eq!(warnings.len(), 4);
+    assert!(warnings.contains(&AuditNotice::Advisory(low)));
+
+    // advisories without a severity count as low, so they only fail alongside low ones
+    let Err(AuditError::Vulnerable(failing)) = audit(
+        &lock(),
+        Some(advisories_dir.path()),
+        Some(AdvisorySeverity::Low),
+    ) else {
+        panic!("audit should fail");
+    };
+    assert_eq!(failing.len(), 2);
+    assert!(failing.contains(&unknown));
+    assert_eq!(
+        audit(
+            &lock(),
+            Some(advisories_dir.path()),
+            Some(AdvisorySeverity::Medium)
+        )
+        .unwrap()
+        .warnings
+        .len(),
+        4
+    );
+
+    write_advisory(advisories_dir.path(), "acme/old", "broken", "title: [");
+    assert!(matches!(
+        audit(&lock(), Some(advisories_dir.path()), None),
+        Err(AuditError::ParseAdvisory(..))
+    ));
+}
+
+#[test]
+fn advisories_dir_missing() {
+    let missing_dir = tempfile::tempdir().unwrap().path().join("nope");
+    assert!(matches!(
+        audit(&lock(), Some(&missing_dir), None),
+        Err(AuditError::AdvisoriesDirMissing(path)) if path == missing_dir
+    ));
+}
+
+#[test]
+fn fail_severity() {
+    assert_eq!(
+        "HIGH".parse::<AdvisorySeverity>().unwrap(),
+        AdvisorySeverity::High
+    );
+    assert!(matches!(
+        "severe".parse::<AdvisorySeverity>(),
+        Err(AuditError::InvalidFailSeverity(value)) if value == "severe"
+    ));
+}
@@ -0,0 +1,79 @@
+use crate::blackfire::{
+    AGENT_PACKAGE, OPT_OUT_ENV_VAR, PROBE_PACKAGE, has_external_agent, is_configured, is_enabled,
+    platform_replace, platform_require,
+};
+use fs_err as fs;
+use libcnb::Env;
+
+#[test]
+fn configured_via_env() {
+    let mut env = Env::new();
+    assert!(!is_configured(&env, None));
+
+    env.insert("BLACKFIRE_SERVER_ID", "id");
+    assert!(!is_configured(&env, None));
+
+    env.insert("BLACKFIRE_SERVER_TOKEN", "");
+    assert!(!is_configured(&env, None));
+
+    env.insert("BLACKFIRE_SERVER_TOKEN", "token");
+    assert!(is_configured(&env, None));
+}
+
+#[test]
+fn configured_via_binding() {
+    let env = Env::new();
+    let bindings = tempfile::tempdir().unwrap();
+    assert!(!is_configured(&env, Some(bindings.path())));
+    assert!(!is_configured(&env, Some(&bindings.path().join("nope"))));
+
+    let other = bindings.path().join("other");
+    fs::create_dir(&other).unwrap();
+    fs::write(other.join("type"), "newrelic").unwrap();
+    assert!(!is_configured(&env, Some(bindings.path())));
+
+    let blackfire = bindings.path().join("my-blackfire");
+    fs::create_dir(&blackfire).unwrap();
+    fs::write(blackfire.join("type"), "blackfire\n").unwrap();
+    assert!(is_configured(&env, Some(bindings.path())));
+}
+
+#[test]
+fn opt_out() {
+    let mut env = Env::new();
+    env.insert("BLACKFIRE_SERVER_ID", "id");
+    env.insert("BLACKFIRE_SERVER_TOKEN", "token");
+    assert!(is_enabled(&env, None));
+
+    env.insert(OPT_OUT_ENV_VAR, "0");
+    assert!(is_enabled(&env, None));
+
+    env.insert(OPT_OUT_ENV_VAR, "yes");
+    assert!(!is_enabled(&env, None));
+    assert!(is_configured(&env, None));
+}
+
+#[test]
+fn external_agent() {
+    let mut env = Env::new();
+    assert!(!has_external_agent(&env));
+    env.insert("BLACKFIRE_AGENT_SOCKET", "");
+    assert!(!has_external_agent(&env));
+    env.insert("BLACKFIRE_AGENT_SOCKET", "tcp://blackfire:8307");
+    assert!(has_external_agent(&env));
+
+    let names = |external_agent| {
+        platform_require(external_agent)
+            .into_iter()
+            .map(|requirement| requirement.name)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(false), [PROBE_PACKAGE, AGENT_PACKAGE]);
+    assert_eq!(names(true), [PROBE_PACKAGE]);
+
+    assert!(platform_replace(false).is_empty());
+    assert_eq!(
+        platform_replace(true).into_iter().collect::<Vec<_>>(),
+        [(AGENT_PACKAGE.to_string(), "*".to_string())]
+    );
+}
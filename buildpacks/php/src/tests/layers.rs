@@ -0,0 +1 @@
+mod php_config;
@@ -0,0 +1,27 @@
+use crate::layers::php_config::find_ini_files;
+use fs_err as fs;
+
+#[test]
+fn ini_files() {
+    let app_dir = tempfile::tempdir().unwrap();
+    let app_dir = app_dir.path();
+    assert!(find_ini_files(app_dir).unwrap().is_empty());
+
+    fs::write(app_dir.join(".user.ini"), "").unwrap();
+    fs::write(app_dir.join("php.ini"), "").unwrap();
+    fs::create_dir(app_dir.join("conf.d")).unwrap();
+    fs::write(app_dir.join("conf.d/20-b.ini"), "").unwrap();
+    fs::write(app_dir.join("conf.d/10-a.ini"), "").unwrap();
+    fs::write(app_dir.join("conf.d/README.md"), "").unwrap();
+    fs::create_dir(app_dir.join("conf.d/dir.ini")).unwrap();
+
+    assert_eq!(
+        find_ini_files(app_dir).unwrap(),
+        vec![
+            app_dir.join("php.ini"),
+            app_dir.join("conf.d/10-a.ini"),
+            app_dir.join("conf.d/20-b.ini"),
+            app_dir.join(".user.ini"),
+        ]
+    );
+}
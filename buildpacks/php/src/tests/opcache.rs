@@ -0,0 +1,115 @@
+use crate::opcache::{OpcachePreloadError, OpcachePreloadNotice, preload_ini, preload_script};
+use composer::ComposerHerokuOpcachePreload;
+use fs_err as fs;
+use std::process::Command;
+
+#[test]
+fn script() {
+    let app_dir = tempfile::tempdir().unwrap();
+    let app_dir = app_dir.path();
+
+    assert_eq!(preload_script(None, app_dir).unwrap().value, None);
+    assert_eq!(
+        preload_script(Some(&ComposerHerokuOpcachePreload::Enabled(false)), app_dir)
+            .unwrap()
+            .value,
+        None
+    );
+
+    let mut notices = Vec::<OpcachePreloadNotice>::new();
+    assert_eq!(
+        preload_script(Some(&ComposerHerokuOpcachePreload::Enabled(true)), app_dir)
+            .unwrap()
+            .unwrap(&mut notices),
+        None
+    );
+    assert_eq!(notices, vec![OpcachePreloadNotice::NoConventionalScript]);
+
+    fs::create_dir_all(app_dir.join("var/cache/prod")).unwrap();
+    fs::write(
+        app_dir.join("var/cache/prod/App_KernelProdContainer.preload.php"),
+        "",
+    )
+    .unwrap();
+    assert_eq!(
+        preload_script(Some(&ComposerHerokuOpcachePreload::Enabled(true)), app_dir)
+            .unwrap()
+            .value,
+        Some(app_dir.join("var/cache/prod/App_KernelProdContainer.preload.php"))
+    );
+
+    fs::create_dir(app_dir.join("config")).unwrap();
+    fs::write(app_dir.join("config/preload.php"), "").unwrap();
+    assert_eq!(
+        preload_script(Some(&ComposerHerokuOpcachePreload::Enabled(true)), app_dir)
+            .unwrap()
+            .value,
+        Some(app_dir.join("config/preload.php"))
+    );
+
+    assert_eq!(
+        preload_script(
+            Some(&ComposerHerokuOpcachePreload::Script(
+                "preload.php".to_string()
+            )),
+            app_dir
+        ),
+        Err(OpcachePreloadError::MissingScript(
+            "preload.php".to_string()
+        ))
+    );
+    fs::write(app_dir.join("preload.php"), "").unwrap();
+    assert_eq!(
+        preload_script(
+            Some(&ComposerHerokuOpcachePreload::Script(
+                "preload.php".to_string()
+            )),
+            app_dir
+        )
+        .unwrap()
+        .value,
+        Some(app_dir.join("preload.php"))
+    );
+}
+
+#[test]
+fn ini() {
+    let app_dir = tempfile::tempdir().unwrap();
+    let app_dir = app_dir.path();
+    // the scripts are owned by the user running the tests
+    let user = String::from_utf8(Command::new("id").arg("-un").output().unwrap().stdout).unwrap();
+    let user = user.trim();
+
+    fs::create_dir(app_dir.join("config")).unwrap();
+    fs::write(app_dir.join("config/preload.php"), "").unwrap();
+    let conventional = preload_script(Some(&ComposerHerokuOpcachePreload::Enabled(true)), app_dir)
+        .unwrap()
+        .value
+        .unwrap();
+    assert_eq!(
+        preload_ini(&conventional),
+        format!(
+            "opcache.preload={}/config/preload.php\nopcache.preload_user={user}\n",
+            app_dir.display()
+        )
+    );
+
+    fs::create_dir(app_dir.join("src")).unwrap();
+    fs::write(app_dir.join("src/preload.php"), "").unwrap();
+    let explicit = preload_script(
+        Some(&ComposerHerokuOpcachePreload::Script(
+            "src/preload.php".to_string(),
+        )),
+        app_dir,
+    )
+    .unwrap()
+    .value
+    .unwrap();
+    assert_eq!(
+        preload_ini(&explicit),
+        format!(
+            "opcache.preload={}/src/preload.php\nopcache.preload_user={user}\n",
+            app_dir.display()
+        )
+    );
+}
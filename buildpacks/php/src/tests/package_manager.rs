@@ -0,0 +1,14 @@
+use crate::package_manager::composer::COMPOSER_RUNTIME_API_VERSION;
+use composer::ComposerVersion;
+
+#[test]
+fn composer_runtime_api_version() {
+    // requirements are only checked against the version if it parses
+    assert_eq!(
+        COMPOSER_RUNTIME_API_VERSION
+            .parse::<ComposerVersion>()
+            .unwrap()
+            .normalized(),
+        "2.2.2.0"
+    );
+}
@@ -0,0 +1,301 @@
+use crate::package_manager::composer::{ComposerSettings, ComposerSettingsError};
+use crate::php_project::{
+    HerokuExtraError, Project, ProjectLoadError, ProjectLoader, ProjectLoaderNotice,
+};
+use crate::tests::EMPTY_LOCK_FIXTURE;
+use ::composer::{ComposerRootPackage, ComposerStability};
+use fs_err as fs;
+use indexmap::IndexMap;
+use libcnb::Env;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use url::Url;
+
+fn project(composer_json: Value) -> Project {
+    Project::new(
+        "composer.json".to_string(),
+        "composer.lock".to_string(),
+        serde_json::from_value::<ComposerRootPackage>(composer_json).unwrap(),
+        None,
+        ComposerSettings::default(),
+    )
+}
+
+#[test]
+fn heroku_extra() {
+    assert_eq!(
+        project(json!({"extra": {"heroku": {"fpm-config": "fpm.conf"}}}))
+            .heroku_extra()
+            .unwrap()
+            .fpm_config
+            .as_deref(),
+        Some("fpm.conf")
+    );
+    assert!(matches!(
+        project(json!({"extra": {"heroku": {"php-config": "short_open_tag=off"}}})).heroku_extra(),
+        Err(HerokuExtraError::Parse(name, _)) if name == "composer.json"
+    ));
+}
+
+#[test]
+fn load_invalid_composer_json() {
+    let app_dir = tempfile::tempdir().unwrap();
+    let loader = ProjectLoader::new("composer.json".to_string(), "composer.lock".to_string());
+
+    fs::write(app_dir.path().join("composer.json"), "{").unwrap();
+    assert!(matches!(
+        loader.load(app_dir.path()),
+        Err(ProjectLoadError::ComposerJsonParse(..))
+    ));
+
+    fs::write(
+        app_dir.path().join("composer.json"),
+        json!({
+            "name": "Acme/App",
+            "require-dev": {"foo bar": "*", "acme/foo": 1},
+            "minimum-stability": "unstable",
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let Err(ProjectLoadError::ComposerJsonInvalid(name, errors)) = loader.load(app_dir.path())
+    else {
+        panic!("composer.json should be invalid");
+    };
+    assert_eq!(name, "composer.json");
+    assert_eq!(
+        errors
+            .iter()
+            .map(|error| error.pointer.as_str())
+            .collect::<Vec<_>>(),
+        vec!["/require-dev/acme~1foo", "/minimum-stability"]
+    );
+
+    // invalid package names alone are only worth a notice
+    fs::write(
+        app_dir.path().join("composer.json"),
+        json!({"name": "Acme/App", "require-dev": {"foo bar": "*"}}).to_string(),
+    )
+    .unwrap();
+    let notices = loader.load(app_dir.path()).unwrap().warnings;
+    let [ProjectLoaderNotice::InvalidPackageNames(name, problems)] = notices.as_slice() else {
+        panic!("expected a notice about invalid package names, got {notices:?}");
+    };
+    assert_eq!(name, "composer.json");
+    assert_eq!(
+        problems
+            .iter()
+            .map(|problem| problem.pointer.as_str())
+            .collect::<Vec<_>>(),
+        vec!["/name", "/require-dev/foo bar"]
+    );
+
+    fs::write(app_dir.path().join("composer.json"), "{}").unwrap();
+    assert!(loader.load(app_dir.path()).unwrap().warnings.is_empty());
+}
+
+#[test]
+fn load_without_lock() {
+    let app_dir = tempfile::tempdir().unwrap();
+    let loader = ProjectLoader::new("composer.json".to_string(), "composer.lock".to_string());
+
+    // platform requirements alone need no lock
+    fs::write(
+        app_dir.path().join("composer.json"),
+        json!({"require": {"php": "8.3.*", "ext-intl": "*", "lib-icu": ">=70"}}).to_string(),
+    )
+    .unwrap();
+    let project = loader.load(app_dir.path()).unwrap().value;
+    assert!(project.composer_lock().is_none());
+    assert_eq!(
+        project
+            .library_requirements()
+            .into_iter()
+            .map(|requirement| (requirement.name, requirement.constraint))
+            .collect::<Vec<_>>(),
+        vec![("lib-icu".to_string(), ">=70".to_string())]
+    );
+
+    fs::write(
+        app_dir.path().join("composer.json"),
+        json!({"require": {"php": "8.3.*", "monolog/monolog": "^3.0"}}).to_string(),
+    )
+    .unwrap();
+    assert!(matches!(
+        loader.load(app_dir.path()),
+        Err(ProjectLoadError::ComposerLockMissing(..))
+    ));
+}
+
+#[test]
+fn load_stale_lock() {
+    let app_dir = tempfile::tempdir().unwrap();
+    let loader = ProjectLoader::new("composer.json".to_string(), "composer.lock".to_string());
+
+    // the fixture's content hash is that of an empty composer.json
+    fs::copy(EMPTY_LOCK_FIXTURE, app_dir.path().join("composer.lock")).unwrap();
+    fs::write(app_dir.path().join("composer.json"), "{}").unwrap();
+    assert!(loader.load(app_dir.path()).unwrap().warnings.is_empty());
+
+    // irrelevant keys do not make the lock stale
+    fs::write(
+        app_dir.path().join("composer.json"),
+        json!({"description": "An app", "scripts": {"test": "phpunit"}}).to_string(),
+    )
+    .unwrap();
+    assert!(loader.load(app_dir.path()).unwrap().warnings.is_empty());
+
+    fs::write(
+        app_dir.path().join("composer.json"),
+        json!({"require": {"php": "8.3.*"}}).to_string(),
+    )
+    .unwrap();
+    let notices = loader.load(app_dir.path()).unwrap().warnings;
+    let [ProjectLoaderNotice::StaleLock(json_name, lock_name)] = notices.as_slice() else {
+        panic!("expected a notice about a stale lock, got {notices:?}");
+    };
+    assert_eq!(json_name, "composer.json");
+    assert_eq!(lock_name, "composer.lock");
+}
+
+#[test]
+fn platform_json_without_lock() {
+    let repositories = [Url::parse(
+        "https://heroku-buildpack-php.s3.dualstack.us-east-1.amazonaws.com/dist-heroku-24-amd64-stable/packages.json",
+    )
+    .unwrap()];
+    let project = project(json!({
+        "require": {"php": "8.4.0@RC", "ext-intl": "*"},
+        "require-dev": {"ext-xdebug": "*"},
+        "minimum-stability": "beta",
+        "prefer-stable": true,
+    }));
+
+    let platform_json = project
+        .platform_json(
+            "heroku-24",
+            &repositories,
+            &[],
+            &IndexMap::new(),
+            false,
+            true,
+        )
+        .unwrap()
+        .value;
+    assert_eq!(
+        platform_json.minimum_stability,
+        Some(ComposerStability::Beta)
+    );
+    assert_eq!(platform_json.prefer_stable, Some(true));
+    let require = platform_json.package.require.unwrap();
+    assert_eq!(
+        require.get("heroku-sys/php").map(String::as_str),
+        Some("8.4.0@RC")
+    );
+    assert_eq!(
+        require.get("heroku-sys/ext-intl").map(String::as_str),
+        Some("*")
+    );
+    assert_eq!(
+        require.get("heroku-sys/composer").map(String::as_str),
+        Some("*")
+    );
+    assert_eq!(
+        platform_json
+            .package
+            .require_dev
+            .unwrap()
+            .get("heroku-sys/ext-xdebug")
+            .map(String::as_str),
+        Some("*")
+    );
+
+    let platform_json = project
+        .platform_json(
+            "heroku-24",
+            &repositories,
+            &[],
+            &IndexMap::new(),
+            false,
+            false,
+        )
+        .unwrap()
+        .value;
+    assert!(platform_json.package.require_dev.is_none());
+}
+
+#[test]
+fn composer_settings() {
+    let app_dir = tempfile::tempdir().unwrap();
+    let load = |composer_json: Value, env: &Env| {
+        fs::write(
+            app_dir.path().join("composer.json"),
+            composer_json.to_string(),
+        )
+        .unwrap();
+        ProjectLoader::from_env(env)
+            .value
+            .load(app_dir.path())
+            .map(|project| project.value.composer_settings().clone())
+    };
+
+    assert_eq!(
+        load(json!({}), &Env::new()).unwrap(),
+        ComposerSettings {
+            vendor_dir: PathBuf::from("vendor"),
+            bin_dir: PathBuf::from("vendor/bin"),
+            process_timeout: 300,
+        }
+    );
+    // the default bin-dir follows the vendor-dir
+    assert_eq!(
+        load(
+            json!({"config": {"vendor-dir": "lib/", "process-timeout": 0}}),
+            &Env::new()
+        )
+        .unwrap(),
+        ComposerSettings {
+            vendor_dir: PathBuf::from("lib"),
+            bin_dir: PathBuf::from("lib/bin"),
+            process_timeout: 0,
+        }
+    );
+
+    // environment variables take precedence over composer.json
+    let mut env = Env::new();
+    env.insert("COMPOSER_VENDOR_DIR", "deps");
+    env.insert("COMPOSER_PROCESS_TIMEOUT", "600");
+    env.insert("COMPOSER_CACHE_DIR", "/tmp/cache");
+    let composer_json =
+        json!({"config": {"vendor-dir": "lib", "bin-dir": "{$vendor-dir}/scripts"}});
+    assert_eq!(
+        load(composer_json.clone(), &env).unwrap(),
+        ComposerSettings {
+            vendor_dir: PathBuf::from("deps"),
+            bin_dir: PathBuf::from("deps/scripts"),
+            process_timeout: 600,
+        }
+    );
+    let notices = ProjectLoader::from_env(&env).warnings;
+    assert!(matches!(
+        notices.as_slice(),
+        [
+            ProjectLoaderNotice::ConfigFromEnvVar(vendor_dir, _, _),
+            ProjectLoaderNotice::ConfigFromEnvVar(process_timeout, _, _),
+            ProjectLoaderNotice::CacheDirFromEnvVarIgnored(_),
+        ] if vendor_dir == "COMPOSER_VENDOR_DIR" && process_timeout == "COMPOSER_PROCESS_TIMEOUT"
+    ));
+
+    env.insert("COMPOSER_BIN_DIR", "bin");
+    assert_eq!(
+        load(composer_json.clone(), &env).unwrap().bin_dir,
+        PathBuf::from("bin")
+    );
+
+    env.insert("COMPOSER_PROCESS_TIMEOUT", "soon");
+    assert!(matches!(
+        load(composer_json, &env),
+        Err(ProjectLoadError::ComposerSettings(ComposerSettingsError::InvalidEnvVar(name, value)))
+            if name == "COMPOSER_PROCESS_TIMEOUT" && value == "soon"
+    ));
+}
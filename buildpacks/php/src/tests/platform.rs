@@ -1,4 +1,8 @@
+mod automatic;
 mod generator;
+mod libraries;
+mod preflight;
+mod repository;
 
 use figment::value::magic::RelativePathBuf;
 use serde::{Deserialize, Serialize};
@@ -18,7 +22,10 @@ struct ComposerLockTestCaseConfig {
     expect_generator_failure: Option<String>,
     expect_finalizer_failure: Option<String>,
     install_dev: bool,
+    apply_php_platform_override: bool,
     repositories: Vec<Url>,
+    /// Add the Blackfire requirements, with an external agent if `true`
+    blackfire_external_agent: Option<bool>,
 }
 
 impl Default for ComposerLockTestCaseConfig {
@@ -36,6 +43,8 @@ impl Default for ComposerLockTestCaseConfig {
             expect_extractor_failure: None,
             expect_finalizer_failure: None,
             install_dev: false,
+            apply_php_platform_override: false,
+            blackfire_external_agent: None,
             repositories: vec![
                 Url::parse(&format!(
                     "https://heroku-buildpack-php.s3.dualstack.us-east-1.amazonaws.com/dist-{stack}-amd64-stable/packages.json",
@@ -0,0 +1,27 @@
+use crate::platform::automatic::{NEWRELIC_OPT_OUT_ENV_VAR, automatic_requirements};
+use libcnb::Env;
+
+fn names(env: &Env) -> Vec<String> {
+    automatic_requirements(env)
+        .into_iter()
+        .map(|requirement| requirement.name)
+        .collect()
+}
+
+#[test]
+fn newrelic() {
+    let mut env = Env::new();
+    assert!(names(&env).is_empty());
+
+    env.insert("NEW_RELIC_LICENSE_KEY", "");
+    assert!(names(&env).is_empty());
+
+    env.insert("NEW_RELIC_LICENSE_KEY", "abc123");
+    assert_eq!(names(&env), vec!["heroku-sys/ext-newrelic"]);
+
+    env.insert(NEWRELIC_OPT_OUT_ENV_VAR, "0");
+    assert_eq!(names(&env), vec!["heroku-sys/ext-newrelic"]);
+
+    env.insert(NEWRELIC_OPT_OUT_ENV_VAR, "yes");
+    assert!(names(&env).is_empty());
+}
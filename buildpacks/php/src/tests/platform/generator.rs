@@ -1,3 +1,4 @@
+use crate::blackfire;
 use crate::package_manager::composer;
 use crate::platform::generator;
 use crate::tests::platform::ComposerLockTestCaseConfig;
@@ -7,7 +8,7 @@ use figment::Figment;
 use figment::providers::{Format, Serialized, Toml};
 use fs_err as fs;
 use rstest::*;
-use serde_json::Value;
+use serde_json::{Value, json};
 use std::collections::HashSet;
 use std::path::PathBuf;
 
@@ -38,7 +39,7 @@ fn make_platform_json(
 
     // FIRST: from the lock file, extract a generator config and packages list
 
-    let generator_input = composer::extract_from_lock(&lock);
+    let generator_input = composer::extract_from_lock(&lock, case.apply_php_platform_override);
 
     // first check: was this even supposed to succeed or fail?
     assert_eq!(
@@ -55,7 +56,7 @@ fn make_platform_json(
 
     // on failure, check if the type of failure what was the test expected
     let mut extractor_notices = Vec::<composer::PlatformExtractorNotice>::new();
-    let generator_input = match generator_input {
+    let mut generator_input = match generator_input {
         Ok(v) => v.unwrap(&mut extractor_notices),
         Err(e) => {
             assert!(
@@ -93,6 +94,21 @@ fn make_platform_json(
 
     // SECOND: generate "platform.json" from the extracted config and packages list
 
+    if let Some(external_agent) = case.blackfire_external_agent {
+        generator_input
+            .additional_require
+            .get_or_insert_default()
+            .extend(
+                blackfire::platform_require(external_agent)
+                    .into_iter()
+                    .map(|requirement| (requirement.name, requirement.constraint)),
+            );
+        generator_input
+            .additional_replace
+            .get_or_insert_default()
+            .extend(blackfire::platform_replace(external_agent));
+    }
+
     let generated_json_package =
         generator::generate_platform_json(&generator_input, &case.stack, &case.repositories);
 
@@ -191,10 +207,15 @@ fn make_platform_json(
         generated_json_package.package.require_dev.take();
     }
 
-    let expected_json_package: ComposerRootPackage = serde_json::from_str(
+    let mut expected_json_package: ComposerRootPackage = serde_json::from_str(
         &fs::read_to_string(case.expected_result.unwrap().relative()).unwrap(),
     )
     .unwrap();
+    // repositories retain the notation they were written in, but we generate them in a normalized form
+    expected_json_package.package.repositories = expected_json_package
+        .package
+        .repositories
+        .map(|repositories| repositories.iter().cloned().collect::<Vec<_>>().into());
 
     let generated_json_value = serde_json::value::to_value(&generated_json_package).unwrap();
     let generated_json_object = generated_json_value.as_object().unwrap();
@@ -267,3 +288,56 @@ fn make_platform_json(
         assert!(comparison.is_ok(), "{}", comparison.unwrap_err());
     }
 }
+
+#[test]
+fn platform_override_mismatches() {
+    let lock = serde_json::from_str(
+        &fs::read_to_string("tests/fixtures/platform/generator/platform-overrides/composer.lock")
+            .unwrap(),
+    )
+    .unwrap();
+    let installed = serde_json::from_value(json!({
+        "packages": [
+            {
+                "name": "heroku-sys/php",
+                "version": "8.2.27",
+                "type": "heroku-sys-php",
+                "provide": {"heroku-sys/ext-intl": "8.2.27", "heroku-sys/ext-json": "*"},
+            },
+        ],
+        "dev": false,
+        "dev-package-names": [],
+    }))
+    .unwrap();
+
+    // ext-intl is bundled with PHP, and ext-mongo was hidden using 'false'
+    assert_eq!(
+        composer::platform_override_mismatches(&lock, &installed, false),
+        vec![
+            composer::PlatformOverrideNotice::InstalledVersionDiffers(
+                "php".to_string(),
+                "8.2.0".to_string(),
+                "8.2.27".to_string()
+            ),
+            composer::PlatformOverrideNotice::InstalledVersionDiffers(
+                "ext-intl".to_string(),
+                "8.2.0".to_string(),
+                "8.2.27".to_string()
+            ),
+        ]
+    );
+    // an applied PHP override was reported already
+    assert_eq!(
+        composer::platform_override_mismatches(&lock, &installed, true).len(),
+        1
+    );
+
+    let installed = serde_json::from_value(json!({
+        "packages": [
+            {"name": "heroku-sys/php", "version": "8.2.0", "type": "heroku-sys-php"},
+            {"name": "heroku-sys/ext-intl", "version": "8.2.0.0", "type": "heroku-sys-php-extension"},
+        ],
+    }))
+    .unwrap();
+    assert!(composer::platform_override_mismatches(&lock, &installed, false).is_empty());
+}
@@ -0,0 +1,125 @@
+use crate::platform::libraries::{
+    LIBRARY_VERSIONS_SCRIPT, LibraryCheckError, LibraryCheckNotice, LibraryRequirement,
+    check_library_requirements, library_requirements, library_version,
+};
+use crate::tests::lock_with;
+use indexmap::IndexMap;
+use serde_json::json;
+
+fn requirement(name: &str, constraint: &str, required_by: Option<&str>) -> LibraryRequirement {
+    LibraryRequirement {
+        name: name.to_string(),
+        constraint: constraint.to_string(),
+        required_by: required_by.map(ToString::to_string),
+    }
+}
+
+#[test]
+fn requirements_from_lock() {
+    let lock = lock_with(json!({
+        "packages": [
+            {
+                "name": "vendor/intl-user",
+                "version": "1.0.0",
+                "require": {"php": "^8.0", "lib-icu": ">=72", "ext-intl": "*"},
+            },
+            {
+                "name": "vendor/other",
+                "version": "1.0.0",
+                "require": {"php": "^8.0"},
+            },
+        ],
+        "packages-dev": [
+            {
+                "name": "vendor/dev-tool",
+                "version": "1.0.0",
+                "require": {"lib-curl": "^8"},
+            },
+        ],
+        "platform": {"php": "^8.1", "lib-openssl": "^3.0"},
+        "platform-dev": {"lib-xsl": "*"},
+    }));
+
+    assert_eq!(
+        library_requirements(&lock),
+        vec![
+            requirement("lib-openssl", "^3.0", None),
+            requirement("lib-icu", ">=72", Some("vendor/intl-user")),
+        ]
+    );
+}
+
+#[test]
+fn library_versions() {
+    let version = |name, raw| library_version(name, raw).map(|v| v.to_string());
+    assert_eq!(
+        version("lib-openssl", "OpenSSL 3.0.13 30 Jan 2024").as_deref(),
+        Some("3.0.13")
+    );
+    assert_eq!(
+        version("lib-openssl", "OpenSSL 1.1.1w  11 Sep 2023").as_deref(),
+        Some("1.1.1.23")
+    );
+    assert_eq!(
+        version("lib-openssl", "OpenSSL 1.0.2za  26 Aug 2021").as_deref(),
+        Some("1.0.2.27")
+    );
+    // only OpenSSL versions get this treatment
+    assert_eq!(version("lib-icu", "74.2a").as_deref(), Some("74.2"));
+    assert_eq!(version("lib-curl", "unknown"), None);
+
+    // Composer's name for the sodium library
+    assert!(LIBRARY_VERSIONS_SCRIPT.contains("$v['lib-libsodium']"));
+    assert!(!LIBRARY_VERSIONS_SCRIPT.contains("'lib-sodium'"));
+}
+
+#[test]
+fn check_requirements() {
+    let installed = IndexMap::from([
+        ("lib-icu".to_string(), "74.2".parse().unwrap()),
+        ("lib-openssl".to_string(), "3.0.13".parse().unwrap()),
+    ]);
+
+    let mut notices = Vec::<LibraryCheckNotice>::new();
+    check_library_requirements(
+        &[
+            requirement("lib-icu", ">=72", Some("vendor/intl-user")),
+            requirement("lib-openssl", "^3.0", None),
+            requirement("lib-curl", "^8", None),
+            requirement("lib-openssl", "not a constraint", None),
+        ],
+        &installed,
+    )
+    .unwrap()
+    .unwrap(&mut notices);
+    assert_eq!(
+        notices,
+        vec![
+            LibraryCheckNotice::UnknownLibraryVersion(requirement("lib-curl", "^8", None)),
+            LibraryCheckNotice::InvalidConstraint(requirement(
+                "lib-openssl",
+                "not a constraint",
+                None
+            )),
+        ]
+    );
+
+    let Err(LibraryCheckError::UnsatisfiedRequirements { requirements }) =
+        check_library_requirements(
+            &[
+                requirement("lib-icu", ">=75", Some("vendor/intl-user")),
+                requirement("lib-openssl", "^3.0", None),
+            ],
+            &installed,
+        )
+    else {
+        panic!("expected unsatisfied requirements");
+    };
+    assert_eq!(
+        requirements,
+        vec![(
+            requirement("lib-icu", ">=75", Some("vendor/intl-user")),
+            "74.2".to_string()
+        )]
+    );
+}
@@ -0,0 +1,129 @@
+use crate::platform::preflight::{PreflightCheckError, check_runtime_requirements};
+use crate::platform::repository::AvailablePlatformPackages;
+use ::composer::ComposerRootPackage;
+use serde_json::json;
+
+fn platform_json(root_constraint: &str, dependency_constraint: &str) -> ComposerRootPackage {
+    serde_json::from_value(json!({
+        "require": {
+            "heroku-sys/php": root_constraint,
+            "vendor/foo": "1.0.0",
+        },
+        "require-dev": {
+            "vendor/bar": "1.0.0",
+        },
+        "repositories": [
+            {
+                "type": "package",
+                "package": [
+                    {
+                        "name": "vendor/foo",
+                        "version": "1.0.0",
+                        "type": "metapackage",
+                        "require": {"heroku-sys/php": dependency_constraint},
+                    },
+                    {
+                        "name": "vendor/bar",
+                        "version": "1.0.0",
+                        "type": "metapackage",
+                        "require": {"heroku-sys/php": "<7"},
+                    },
+                ],
+            },
+        ],
+    }))
+    .unwrap()
+}
+
+#[test]
+fn runtime_requirements() {
+    let available = AvailablePlatformPackages {
+        runtimes: ["8.2.30", "8.3.30", "8.4.21"]
+            .into_iter()
+            .map(|version| version.parse().unwrap())
+            .collect(),
+        ..Default::default()
+    };
+
+    for (root, dependency) in [
+        ("*", "*"),
+        ("~8.3.0", ">=8.1"),
+        ("^8.2", "8.4.*"),
+        ("7.4.* || 8.3.*", ">= 8.2 <8.4"),
+        ("8.4.21@RC", "^7.4 | ^8.0"),
+        ("^8.2", "^7.4 ~"), // invalid, so only the root requirement is checked
+        ("^7.4 ~", "^8.3"),
+    ] {
+        assert!(
+            check_runtime_requirements(
+                &platform_json(root, dependency),
+                &available,
+                "heroku-24",
+                "amd64"
+            )
+            .is_ok(),
+            "{root} and {dependency}"
+        );
+    }
+
+    for (root, dependency) in [
+        ("~7.4", "*"),
+        ("^8.2", "8.1.*"),
+        ("8.3.*", ">8.3.30"),
+        ("^8.4.22", ">=8.1"),
+        ("~7.4", "^7.4 || dev-main"),
+    ] {
+        let Err(PreflightCheckError::UnsatisfiableRuntimeRequirement {
+            requirements,
+            available_versions,
+            ..
+        }) = check_runtime_requirements(
+            &platform_json(root, dependency),
+            &available,
+            "heroku-24",
+            "amd64",
+        )
+        else {
+            panic!("{root} and {dependency} should not be satisfiable");
+        };
+        assert_eq!(
+            requirements,
+            [
+                (None, root.to_string()),
+                (Some("vendor/foo".to_string()), dependency.to_string())
+            ]
+        );
+        assert_eq!(available_versions, ["8.2.30", "8.3.30", "8.4.21"]);
+    }
+}
+
+#[test]
+fn invalid_runtime_requirements() {
+    let available = AvailablePlatformPackages {
+        runtimes: ["8.3.30".parse().unwrap()].into_iter().collect(),
+        ..Default::default()
+    };
+
+    // the valid requirements are still checked, and the invalid ones are not reported
+    let Err(PreflightCheckError::UnsatisfiableRuntimeRequirement { requirements, .. }) =
+        check_runtime_requirements(
+            &platform_json("~7.4", "^7.4 ~"),
+            &available,
+            "heroku-24",
+            "amd64",
+        )
+    else {
+        panic!("~7.4 should not be satisfiable");
+    };
+    assert_eq!(requirements, [(None, "~7.4".to_string())]);
+
+    assert!(
+        check_runtime_requirements(
+            &platform_json(">=8.0 <", "dev-main ||"),
+            &available,
+            "heroku-24",
+            "amd64"
+        )
+        .is_ok()
+    );
+}
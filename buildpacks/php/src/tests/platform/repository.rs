@@ -0,0 +1,69 @@
+use crate::platform::repository;
+use ::composer::ComposerVersion;
+use fs_err as fs;
+use indexmap::IndexSet;
+use url::Url;
+
+fn fixture_url(name: &str, query: Option<&str>) -> Url {
+    let mut url = Url::from_file_path(
+        fs::canonicalize(format!("tests/fixtures/platform/repository/{name}.json")).unwrap(),
+    )
+    .unwrap();
+    url.set_query(query);
+    url
+}
+
+fn versions(versions: &IndexSet<ComposerVersion>) -> Vec<String> {
+    versions.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn available_platform_packages() {
+    let available = repository::available_platform_packages(&[
+        fixture_url("default", None),
+        fixture_url("custom", Some("composer-repository-only=ext-foo")),
+    ])
+    .unwrap();
+
+    assert_eq!(versions(&available.runtimes), ["8.3.30", "8.4.21"]);
+    assert_eq!(
+        available.extensions.keys().collect::<Vec<_>>(),
+        ["ext-foo", "ext-redis"]
+    );
+    // the custom repository is canonical, so it hides ext-foo from the default repository
+    assert_eq!(versions(&available.extensions["ext-foo"]), ["2.0.0"]);
+    assert_eq!(
+        available.to_string(),
+        "PHP: 8.3.30, 8.4.21\nExtensions: ext-foo, ext-redis"
+    );
+
+    let available = repository::available_platform_packages(&[
+        fixture_url("default", None),
+        fixture_url(
+            "custom",
+            Some("composer-repository-canonical=0&composer-repository-exclude=php,ext-bar"),
+        ),
+    ])
+    .unwrap();
+
+    // versions are sorted, even though the custom repository was loaded first
+    assert_eq!(
+        versions(&available.extensions["ext-foo"]),
+        ["1.0.0", "2.0.0"]
+    );
+}
+
+#[test]
+fn lazy_available_platform_packages() {
+    let repositories = [fixture_url("default", None)];
+    let lazy = repository::LazyAvailablePlatformPackages::new(&repositories);
+    assert_eq!(
+        versions(&lazy.get().unwrap().runtimes),
+        ["8.3.30", "8.4.21"]
+    );
+
+    // failures are not fatal, since none of the uses are essential
+    let repositories = [Url::parse("file:///nonexistent/packages.json").unwrap()];
+    let lazy = repository::LazyAvailablePlatformPackages::new(&repositories);
+    assert!(lazy.get().is_none());
+}
@@ -0,0 +1,88 @@
+use crate::sbom::{SbomComponent, composer_lock_components, cyclonedx_sbom, platform_components};
+use crate::tests::lock_with;
+use libcnb::data::sbom::SbomFormat;
+use serde_json::{Value, json};
+
+#[test]
+fn platform() {
+    let installed_json = json!({
+        "packages": [
+            {"name": "heroku/installer-plugin", "version": "1.0.0", "type": "composer-plugin"},
+            {"name": "heroku-sys/php", "version": "8.3.12", "type": "heroku-sys-php"},
+            {"name": "heroku-sys/ext-redis", "version": "6.0.2", "type": "heroku-sys-php-extension"},
+            {"name": "heroku-sys/nginx", "version": "1.26.2", "type": "heroku-sys-program"},
+            {"name": "composer.json/composer.lock", "version": "dev-abc123", "type": "metapackage"},
+        ],
+        "dev": false,
+        "dev-package-names": [],
+    });
+
+    assert_eq!(
+        platform_components(&serde_json::from_value(installed_json).unwrap()),
+        vec![
+            SbomComponent {
+                kind: "application",
+                name: "php".to_string(),
+                version: "8.3.12".to_string(),
+                purl: Some("pkg:generic/php@8.3.12".to_string()),
+            },
+            SbomComponent {
+                kind: "library",
+                name: "ext-redis".to_string(),
+                version: "6.0.2".to_string(),
+                purl: Some("pkg:generic/ext-redis@6.0.2".to_string()),
+            },
+            SbomComponent {
+                kind: "application",
+                name: "nginx".to_string(),
+                version: "1.26.2".to_string(),
+                purl: Some("pkg:generic/nginx@1.26.2".to_string()),
+            },
+        ]
+    );
+
+    // the format of Composer 1 is also understood
+    assert_eq!(
+        platform_components(&serde_json::from_value(json!([])).unwrap()),
+        vec![]
+    );
+}
+
+#[test]
+fn composer_lock() {
+    let lock = lock_with(json!({
+        "packages": [
+            {"name": "monolog/monolog", "version": "3.7.0"},
+            {"name": "acme/widgets", "version": "dev-feature/foo"},
+        ],
+        "packages-dev": [
+            {"name": "phpunit/phpunit", "version": "11.3.6"},
+        ],
+    }));
+
+    let components = composer_lock_components(&lock);
+    assert_eq!(
+        components
+            .iter()
+            .map(|component| component.purl.as_deref())
+            .collect::<Vec<_>>(),
+        vec![
+            Some("pkg:composer/monolog/monolog@3.7.0"),
+            Some("pkg:composer/acme/widgets@dev-feature%2Ffoo"),
+        ]
+    );
+
+    let sbom = cyclonedx_sbom(&components);
+    assert!(matches!(sbom.format, SbomFormat::CycloneDxJson));
+    let sbom = serde_json::from_slice::<Value>(&sbom.data).unwrap();
+    assert_eq!(sbom["bomFormat"], "CycloneDX");
+    assert_eq!(
+        sbom["components"][0],
+        json!({
+            "type": "library",
+            "name": "monolog/monolog",
+            "version": "3.7.0",
+            "purl": "pkg:composer/monolog/monolog@3.7.0",
+        })
+    );
+}
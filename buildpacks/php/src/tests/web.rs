@@ -0,0 +1,76 @@
+use crate::web::{
+    WebProcessError, WebServer, WebServerNotice, boot_script_args, web_server, web_server_engines,
+};
+use composer::ComposerHerokuExtra;
+use fs_err as fs;
+use indexmap::IndexMap;
+
+#[test]
+fn engines() {
+    let mut extra = ComposerHerokuExtra::default();
+    assert!(web_server_engines(&extra).value.is_empty());
+
+    extra.engines = IndexMap::from([
+        ("php".to_string(), "^8.3".to_string()),
+        ("nginx".to_string(), "^1.28".to_string()),
+    ]);
+    let engines = web_server_engines(&extra);
+    assert_eq!(
+        engines.value,
+        IndexMap::from([(WebServer::Nginx, "^1.28".to_string())])
+    );
+    assert_eq!(
+        engines.warnings,
+        vec![WebServerNotice::UnknownEngine("php".to_string())]
+    );
+}
+
+#[test]
+fn select_web_server() {
+    let mut engines = IndexMap::new();
+    assert_eq!(web_server(&engines), WebServer::Apache);
+
+    engines.insert(WebServer::Nginx, "*".to_string());
+    assert_eq!(web_server(&engines), WebServer::Nginx);
+
+    engines.insert(WebServer::Apache, "^2.4".to_string());
+    assert_eq!(web_server(&engines), WebServer::Apache);
+}
+
+#[test]
+fn args() {
+    let app_dir = tempfile::tempdir().unwrap();
+    let app_dir = app_dir.path();
+    fs::write(app_dir.join("apache.conf"), "").unwrap();
+    fs::write(app_dir.join("fpm.conf"), "").unwrap();
+
+    let mut extra = ComposerHerokuExtra::default();
+    assert!(
+        boot_script_args(&extra, WebServer::Apache, app_dir)
+            .unwrap()
+            .is_empty()
+    );
+
+    extra.apache_includes = vec!["apache.conf".to_string()];
+    extra.nginx_includes = vec!["nginx.conf".to_string()];
+    extra.fpm_config = Some("fpm.conf".to_string());
+    assert_eq!(
+        boot_script_args(&extra, WebServer::Apache, app_dir).unwrap(),
+        vec!["-C", "apache.conf", "-F", "fpm.conf"]
+    );
+    assert_eq!(
+        boot_script_args(&extra, WebServer::Nginx, app_dir),
+        Err(WebProcessError::MissingFile {
+            key: "nginx-includes".to_string(),
+            path: "nginx.conf".to_string()
+        })
+    );
+
+    extra.apache_includes.push("other.conf".to_string());
+    assert_eq!(
+        boot_script_args(&extra, WebServer::Apache, app_dir),
+        Err(WebProcessError::MultipleIncludes(
+            "apache-includes".to_string()
+        ))
+    );
+}
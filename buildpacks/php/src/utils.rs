@@ -1,4 +1,5 @@
 use flate2::read::GzDecoder;
+use libcnb::Env;
 use std::io;
 use std::path::{Component, Path, PathBuf};
 use std::time::Duration;
@@ -83,7 +84,7 @@ fn download(uri: &str) -> Result<Box<dyn io::Read + Send + Sync + 'static>, Down
         .into_reader())
 }
 
-fn download_with_retry(
+pub(crate) fn download_with_retry(
     uri: &str,
 ) -> Result<Box<dyn io::Read + Send + Sync + 'static>, DownloadUnpackError> {
     let backoff =
@@ -116,3 +117,25 @@ pub(crate) fn add_prefix_to_non_empty<P: Into<Vec<u8>>>(prefix: P) -> impl Fn(Ve
         }
     }
 }
+
+/// Checks whether the given (typically user-supplied) value is one of the usual truthy strings
+/// such as `1`, `true`, `on` or `yes` (case-insensitive, ignoring surrounding whitespace).
+pub(crate) fn is_truthy(value: impl AsRef<str>) -> bool {
+    matches!(
+        value.as_ref().trim().to_ascii_lowercase().as_ref(),
+        "1" | "true" | "on" | "yes"
+    )
+}
+
+/// Returns the directory containing service bindings, if any.
+///
+/// That is `$SERVICE_BINDING_ROOT` if the platform sets it, or the `bindings` directory in the
+/// platform directory otherwise (which is where bindings live during a build).
+pub(crate) fn service_binding_root(env: &Env) -> Option<PathBuf> {
+    env.get("SERVICE_BINDING_ROOT")
+        .map(PathBuf::from)
+        .or_else(|| {
+            env.get("CNB_PLATFORM_DIR")
+                .map(|dir| PathBuf::from(dir).join("bindings"))
+        })
+}
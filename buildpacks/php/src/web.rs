@@ -0,0 +1,118 @@
+use composer::ComposerHerokuExtra;
+use indexmap::IndexMap;
+use libcnb::data::launch::{Process, ProcessBuilder};
+use libcnb::data::process_type;
+use std::path::Path;
+use warned::Warned;
+
+/// A web server that the boot scripts can run.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum WebServer {
+    Apache,
+    Nginx,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum WebServerNotice {
+    /// The given `extra.heroku.engines` entry is not a web server, and was ignored
+    UnknownEngine(String),
+}
+
+/// The web server version constraints from `engines` in the given [`ComposerHerokuExtra`].
+///
+/// Entries for anything other than a [`WebServer`] (like `php`, which is selected using `require`)
+/// are ignored, with a [`WebServerNotice`].
+pub(crate) fn web_server_engines(
+    heroku_extra: &ComposerHerokuExtra,
+) -> Warned<IndexMap<WebServer, String>, WebServerNotice> {
+    let mut notices = vec![];
+    let engines = heroku_extra
+        .engines
+        .iter()
+        .filter_map(|(name, constraint)| {
+            let web_server = match name.as_str() {
+                "apache" => WebServer::Apache,
+                "nginx" => WebServer::Nginx,
+                _ => {
+                    notices.push(WebServerNotice::UnknownEngine(name.clone()));
+                    return None;
+                }
+            };
+            Some((web_server, constraint.clone()))
+        })
+        .collect();
+    Warned::new(engines, notices)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum WebProcessError {
+    /// A file referenced by the given `extra.heroku` key does not exist
+    MissingFile { key: String, path: String },
+    /// More than one file is listed in the given `extra.heroku` key; the boot scripts take only one
+    MultipleIncludes(String),
+}
+
+/// Selects the web server for the default process from the given [web server engines](web_server_engines).
+///
+/// Apache is the default; Nginx is used if it is the only web server listed in `engines`.
+pub(crate) fn web_server(engines: &IndexMap<WebServer, String>) -> WebServer {
+    if engines.contains_key(&WebServer::Nginx) && !engines.contains_key(&WebServer::Apache) {
+        WebServer::Nginx
+    } else {
+        WebServer::Apache
+    }
+}
+
+/// Builds the arguments for the boot script of the given [`WebServer`] from the given [`ComposerHerokuExtra`].
+///
+/// All referenced files are checked for existence in the given app directory.
+pub(crate) fn boot_script_args(
+    heroku_extra: &ComposerHerokuExtra,
+    web_server: WebServer,
+    app_dir: &Path,
+) -> Result<Vec<String>, WebProcessError> {
+    let check_file = |key: &str, path: &String| {
+        if app_dir.join(path).is_file() {
+            Ok(path.clone())
+        } else {
+            Err(WebProcessError::MissingFile {
+                key: key.to_string(),
+                path: path.clone(),
+            })
+        }
+    };
+
+    let (includes_key, includes) = match web_server {
+        WebServer::Apache => ("apache-includes", &heroku_extra.apache_includes),
+        WebServer::Nginx => ("nginx-includes", &heroku_extra.nginx_includes),
+    };
+
+    let mut args = vec![];
+    match includes.as_slice() {
+        [] => (),
+        [include] => args.extend(["-C".to_string(), check_file(includes_key, include)?]),
+        _ => return Err(WebProcessError::MultipleIncludes(includes_key.to_string())),
+    }
+    if let Some(fpm_config) = &heroku_extra.fpm_config {
+        args.extend(["-F".to_string(), check_file("fpm-config", fpm_config)?]);
+    }
+
+    Ok(args)
+}
+
+/// Builds the default `web` [`Process`] running the given [`WebServer`] for the given [`ComposerHerokuExtra`].
+pub(crate) fn default_process(
+    heroku_extra: &ComposerHerokuExtra,
+    web_server: WebServer,
+    app_dir: &Path,
+) -> Result<Process, WebProcessError> {
+    let boot_script = match web_server {
+        WebServer::Apache => "heroku-php-apache2",
+        WebServer::Nginx => "heroku-php-nginx",
+    };
+
+    Ok(ProcessBuilder::new(process_type!("web"), [boot_script])
+        .args(boot_script_args(heroku_extra, web_server, app_dir)?)
+        .default(true)
+        .build())
+}
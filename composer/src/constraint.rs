@@ -0,0 +1,662 @@
+//! Parsing and matching of Composer version constraints.
+//!
+//! The behavior mirrors that of `Composer\Semver\VersionParser` and `Composer\Semver\Constraint`,
+//! including their semantics for comparisons of dev branches.
+
+use crate::version::{normalize, parse_stability, php_version_compare};
+use crate::{ComposerStability, ComposerVersion};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// A comparison operator in a [`ComposerConstraint`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ComposerConstraintOperator {
+    Equal,
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+}
+
+impl ComposerConstraintOperator {
+    fn from_php(operator: &str) -> Option<Self> {
+        Some(match operator {
+            "" | "=" | "==" => Self::Equal,
+            "!=" | "<>" => Self::NotEqual,
+            "<" => Self::LessThan,
+            "<=" => Self::LessThanOrEqual,
+            ">" => Self::GreaterThan,
+            ">=" => Self::GreaterThanOrEqual,
+            _ => return None,
+        })
+    }
+
+    fn test(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Equal => ordering.is_eq(),
+            Self::NotEqual => ordering.is_ne(),
+            Self::LessThan => ordering.is_lt(),
+            Self::LessThanOrEqual => ordering.is_le(),
+            Self::GreaterThan => ordering.is_gt(),
+            Self::GreaterThanOrEqual => ordering.is_ge(),
+        }
+    }
+}
+
+impl fmt::Display for ComposerConstraintOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::LessThan => "<",
+            Self::LessThanOrEqual => "<=",
+            Self::GreaterThan => ">",
+            Self::GreaterThanOrEqual => ">=",
+        })
+    }
+}
+
+/// The parsed, normalized form of a [`ComposerVersionConstraint`].
+///
+/// The [`Display`](fmt::Display) output is identical to that of Composer's constraint classes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ComposerConstraint {
+    /// Matches any version, including dev branches (`*`)
+    MatchAll,
+    /// Compares against a normalized version, e.g. `>= 1.0.0.0-dev`
+    Single(ComposerConstraintOperator, String),
+    /// A list of constraints that must all (if `conjunctive`) or any match
+    Multi {
+        constraints: Vec<ComposerConstraint>,
+        conjunctive: bool,
+    },
+}
+
+impl ComposerConstraint {
+    fn from_list(mut constraints: Vec<ComposerConstraint>, conjunctive: bool) -> Self {
+        if constraints.len() == 1 {
+            constraints.remove(0)
+        } else {
+            Self::Multi {
+                constraints,
+                conjunctive,
+            }
+        }
+    }
+
+    /// Checks whether the given normalized version satisfies this constraint.
+    fn matches_normalized(&self, version: &str) -> bool {
+        match self {
+            Self::MatchAll => true,
+            Self::Single(operator, bound) => {
+                let version_is_branch = version.starts_with("dev-");
+                let bound_is_branch = bound.starts_with("dev-");
+                match operator {
+                    ComposerConstraintOperator::NotEqual
+                        if version_is_branch || bound_is_branch =>
+                    {
+                        version != bound
+                    }
+                    _ if version_is_branch && bound_is_branch => {
+                        *operator == ComposerConstraintOperator::Equal && version == bound
+                    }
+                    // dev branches are not comparable to anything but themselves
+                    _ if version_is_branch || bound_is_branch => false,
+                    _ => operator.test(php_version_compare(version, bound)),
+                }
+            }
+            Self::Multi {
+                constraints,
+                conjunctive: true,
+            } => constraints.iter().all(|c| c.matches_normalized(version)),
+            Self::Multi {
+                constraints,
+                conjunctive: false,
+            } => constraints.iter().any(|c| c.matches_normalized(version)),
+        }
+    }
+}
+
+impl fmt::Display for ComposerConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MatchAll => f.write_str("*"),
+            Self::Single(operator, version) => write!(f, "{operator} {version}"),
+            Self::Multi {
+                constraints,
+                conjunctive,
+            } => write!(
+                f,
+                "[{}]",
+                constraints
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(if *conjunctive { " " } else { " || " })
+            ),
+        }
+    }
+}
+
+/// A version constraint as used in `require`, `conflict`, `provide` etc., like `^8.1 || ~7.4.3`.
+///
+/// Serializes and deserializes as the original ("pretty") string.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ComposerVersionConstraint {
+    pretty: String,
+    constraint: ComposerConstraint,
+}
+
+impl ComposerVersionConstraint {
+    /// The parsed, normalized constraint.
+    #[must_use]
+    pub fn constraint(&self) -> &ComposerConstraint {
+        &self.constraint
+    }
+
+    /// Checks whether the given [`ComposerVersion`] satisfies this constraint.
+    #[must_use]
+    pub fn matches(&self, version: &ComposerVersion) -> bool {
+        self.constraint.matches_normalized(version.normalized())
+    }
+}
+
+impl fmt::Display for ComposerVersionConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pretty)
+    }
+}
+
+impl FromStr for ComposerVersionConstraint {
+    type Err = ComposerVersionConstraintError;
+
+    fn from_str(constraints: &str) -> Result<Self, Self::Err> {
+        let error = |reason| ComposerVersionConstraintError {
+            constraint: constraints.to_string(),
+            reason,
+        };
+
+        let mut or_groups = Vec::new();
+        for or_constraint in regex!(r"\s*\|\|?\s*").split(constraints.trim()) {
+            let mut and_group = Vec::new();
+            for and_constraint in split_and_constraints(or_constraint) {
+                and_group.extend(parse_constraint(and_constraint).map_err(error)?);
+            }
+            or_groups.push(ComposerConstraint::from_list(and_group, true));
+        }
+
+        Ok(Self {
+            pretty: constraints.to_string(),
+            constraint: ComposerConstraint::from_list(or_groups, false),
+        })
+    }
+}
+
+impl TryFrom<String> for ComposerVersionConstraint {
+    type Error = ComposerVersionConstraintError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ComposerVersionConstraint> for String {
+    fn from(value: ComposerVersionConstraint) -> Self {
+        value.pretty
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComposerVersionConstraintError {
+    constraint: String,
+    reason: String,
+}
+
+impl fmt::Display for ComposerVersionConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Could not parse version constraint {}: {}",
+            self.constraint, self.reason
+        )
+    }
+}
+
+impl std::error::Error for ComposerVersionConstraintError {}
+
+/// Splits a constraint into its AND-ed parts, which are separated by a comma or by whitespace.
+///
+/// Whitespace after operators (`>= 1.0`), in hyphen ranges (`1.0 - 2.0`) and around `as` aliases
+/// (`dev-main as 1.0.x-dev`) does not separate constraints.
+fn split_and_constraints(constraints: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut rest = constraints;
+    while let Some(run_start) = rest
+        .find([' ', ','])
+        .map(|i| constraints.len() - rest.len() + i)
+    {
+        let run_end = constraints[run_start..]
+            .find(|c| c != ' ' && c != ',')
+            .map_or(constraints.len(), |i| run_start + i);
+        let (before, run, after) = (
+            &constraints[..run_start],
+            &constraints[run_start..run_end],
+            &constraints[run_end..],
+        );
+        if !before.is_empty()
+            && !after.is_empty()
+            && run.matches(',').count() <= 1
+            && !before.ends_with(['=', '<', '>', '-'])
+            && !before.ends_with("as")
+            && !after.starts_with('-')
+            && !after.starts_with("as")
+        {
+            parts.push(&constraints[start..run_start]);
+            start = run_end;
+        }
+        rest = after;
+    }
+    parts.push(&constraints[start..]);
+    parts
+}
+
+/// Strips off aliasing, stability flags and `#refs` from a single constraint, and returns the
+/// remaining constraint and the stability flag, unless that was `@stable`.
+fn strip_constraint_suffixes(constraint: &str) -> (&str, Option<&str>) {
+    let mut constraint = constraint;
+    let mut stability_modifier = None;
+
+    // strip off aliasing
+    if let Some(captures) = regex!(r"^([^,\s]+) +as +[^,\s]+$").captures(constraint) {
+        constraint = captures.get(1).map_or("", |m| m.as_str());
+    }
+    // strip off stability flags, but remember them for use with comparison operators
+    if let Some(captures) =
+        regex!(r"(?i)^([^,\s]*?)@(stable|RC|beta|alpha|dev)$").captures(constraint)
+    {
+        constraint = captures.get(1).map_or("", |m| m.as_str());
+        if constraint.is_empty() {
+            constraint = "*";
+        }
+        stability_modifier = captures
+            .get(2)
+            .map(|m| m.as_str())
+            .filter(|&stability| stability != "stable");
+    }
+    // strip off #refs, as those are only relevant for installation
+    if let Some(captures) =
+        regex!(r"(?i)^(dev-[^,\s@]+?|[^,\s@]+?\.x-dev)#.+$").captures(constraint)
+    {
+        constraint = captures.get(1).map_or("", |m| m.as_str());
+    }
+
+    (constraint, stability_modifier)
+}
+
+/// Parses a single constraint (without `,` or `||`) into one or two [`ComposerConstraint`]s.
+fn parse_constraint(constraint: &str) -> Result<Vec<ComposerConstraint>, String> {
+    use ComposerConstraintOperator::{GreaterThanOrEqual, LessThan, LessThanOrEqual};
+
+    let (constraint, stability_modifier) = strip_constraint_suffixes(constraint);
+
+    if let Some(captures) = regex!(r"(?i)^(v)?[x*](\.[x*])*$").captures(constraint) {
+        return Ok(vec![
+            if captures.get(1).is_some() || captures.get(2).is_some() {
+                ComposerConstraint::Single(GreaterThanOrEqual, "0.0.0.0-dev".to_string())
+            } else {
+                ComposerConstraint::MatchAll
+            },
+        ]);
+    }
+
+    // tilde range, e.g. `~1.2`: the last given version component may change
+    if let Some(captures) = regex!(r"(?i)^~>?v?(\d+)(?:\.(\d+))?(?:\.(\d+))?(?:\.(\d+))?[._-]?(?:(stable|beta|b|RC|alpha|a|patch|pl|p)((?:[.-]?\d+)*)?)?([.-]?dev)?(?:\+[^\s]+)?$").captures(constraint) {
+        if constraint.starts_with("~>") {
+            return Err(
+                r#"Invalid operator "~>", you probably meant to use the "~" operator"#.to_string(),
+            );
+        }
+        let components = version_components(&captures, 1);
+        let position = components.iter().rposition(Option::is_some).unwrap_or(0) + 1;
+        let stability_suffix = stability_suffix(&captures, 5, 7);
+        return Ok(vec![
+            ComposerConstraint::Single(
+                GreaterThanOrEqual,
+                normalize(&format!("{}{stability_suffix}", &constraint[1..]))?,
+            ),
+            ComposerConstraint::Single(
+                LessThan,
+                manipulate_version(&components, position.saturating_sub(1).max(1), 1) + "-dev",
+            ),
+        ]);
+    }
+
+    // caret range, e.g. `^1.2`: changes that do not break backwards compatibility
+    if let Some(captures) = regex!(r"(?i)^\^v?(\d+)(?:\.(\d+))?(?:\.(\d+))?(?:\.(\d+))?[._-]?(?:(stable|beta|b|RC|alpha|a|patch|pl|p)((?:[.-]?\d+)*)?)?([.-]?dev)?(?:\+[^\s]+)?$").captures(constraint) {
+        let components = version_components(&captures, 1);
+        let position = match components {
+            [Some("0"), Some("0"), Some(_), _] => 3,
+            [Some("0"), Some(_), _, _] => 2,
+            _ => 1,
+        };
+        let stability_suffix = stability_suffix(&captures, 5, 7);
+        return Ok(vec![
+            ComposerConstraint::Single(
+                GreaterThanOrEqual,
+                normalize(&format!("{}{stability_suffix}", &constraint[1..]))?,
+            ),
+            ComposerConstraint::Single(
+                LessThan,
+                manipulate_version(&components, position, 1) + "-dev",
+            ),
+        ]);
+    }
+
+    // wildcard range, e.g. `1.2.*`
+    if let Some(captures) =
+        regex!(r"^v?(\d+)(?:\.(\d+))?(?:\.(\d+))?(?:\.[xX*])+$").captures(constraint)
+    {
+        let components = version_components(&captures, 1);
+        let position = components.iter().rposition(Option::is_some).unwrap_or(0) + 1;
+        let low_version = manipulate_version(&components, position, 0) + "-dev";
+        let high_version = manipulate_version(&components, position, 1) + "-dev";
+        return Ok(if low_version == "0.0.0.0-dev" {
+            vec![ComposerConstraint::Single(LessThan, high_version)]
+        } else {
+            vec![
+                ComposerConstraint::Single(GreaterThanOrEqual, low_version),
+                ComposerConstraint::Single(LessThan, high_version),
+            ]
+        });
+    }
+
+    // hyphen range, e.g. `1.0 - 2.0`: an inclusive range, with partial upper bounds as wildcards
+    if let Some(captures) = regex!(r"(?i)^(v?(\d+)(?:\.(\d+))?(?:\.(\d+))?(?:\.(\d+))?[._-]?(?:(stable|beta|b|RC|alpha|a|patch|pl|p)((?:[.-]?\d+)*)?)?([.-]?dev)?(?:\+[^\s]+)?) +- +(v?(\d+)(?:\.(\d+))?(?:\.(\d+))?(?:\.(\d+))?[._-]?(?:(stable|beta|b|RC|alpha|a|patch|pl|p)((?:[.-]?\d+)*)?)?([.-]?dev)?(?:\+[^\s]+)?)$").captures(constraint) {
+        let group = |i| captures.get(i).map_or("", |m| m.as_str());
+        let low_version = normalize(group(1))? + stability_suffix(&captures, 6, 8);
+        let high_components = version_components(&captures, 10);
+        let high_version = normalize(group(9))?;
+        return Ok(vec![
+            ComposerConstraint::Single(GreaterThanOrEqual, low_version),
+            if (high_components[1].is_some() && high_components[2].is_some())
+                || captures.get(14).is_some()
+                || captures.get(16).is_some()
+            {
+                ComposerConstraint::Single(LessThanOrEqual, high_version)
+            } else {
+                let position = if high_components[1].is_some() { 2 } else { 1 };
+                ComposerConstraint::Single(
+                    LessThan,
+                    manipulate_version(&high_components, position, 1) + "-dev",
+                )
+            },
+        ]);
+    }
+
+    parse_basic_constraint(constraint, stability_modifier)
+}
+
+/// Parses a basic comparison, e.g. `>=1.0` or `1.0.0`, with an optional stability modifier that
+/// was given as a flag, e.g. `@beta` in `>=1.0@beta`.
+fn parse_basic_constraint(
+    constraint: &str,
+    stability_modifier: Option<&str>,
+) -> Result<Vec<ComposerConstraint>, String> {
+    if let Some(captures) = regex!(r"^(<>|!=|>=?|<=?|==?)?\s*(.*)$").captures(constraint) {
+        let operator_string = captures.get(1).map_or("", |m| m.as_str());
+        let version_string = captures.get(2).map_or("", |m| m.as_str());
+        let operator = ComposerConstraintOperator::from_php(operator_string)
+            .ok_or_else(|| format!("Invalid operator \"{operator_string}\""))?;
+
+        let mut version = normalize(version_string).or_else(|e| {
+            // recover from an invalid constraint like `foobar-dev`, which should be `dev-foobar`
+            match version_string.strip_suffix("-dev") {
+                Some(branch) if regex!(r"^[0-9a-zA-Z-./]+$").is_match(version_string) => {
+                    normalize(&format!("dev-{branch}"))
+                }
+                _ => Err(e),
+            }
+        })?;
+
+        if let Some(stability) = stability_modifier.filter(|_| {
+            operator != ComposerConstraintOperator::Equal
+                && parse_stability(&version) == ComposerStability::Stable
+        }) {
+            version = format!("{version}-{stability}");
+        } else if matches!(operator_string, "<" | ">=")
+            && !regex!(
+                r"-[._-]?(?:(stable|beta|b|rc|alpha|a|patch|pl|p)((?:[.-]?\d+)*)?)?([.-]?dev)?$"
+            )
+            .is_match(&version_string.to_lowercase())
+            && !version_string.starts_with("dev-")
+        {
+            // a lower bound of `>=1.0` should also match `1.0-beta`, an upper bound of `<2.0` should not
+            version.push_str("-dev");
+        }
+
+        return Ok(vec![ComposerConstraint::Single(operator, version)]);
+    }
+
+    Err(format!("Could not parse version constraint {constraint}"))
+}
+
+/// Returns the four numeric version components captured starting at the given group index.
+fn version_components<'a>(captures: &regex::Captures<'a>, first: usize) -> [Option<&'a str>; 4] {
+    std::array::from_fn(|i| captures.get(first + i).map(|m| m.as_str()))
+}
+
+/// Returns `-dev` if neither a stability nor a `dev` suffix was captured in the given groups.
+fn stability_suffix(captures: &regex::Captures, stability: usize, dev: usize) -> &'static str {
+    if captures.get(stability).is_none() && captures.get(dev).is_none() {
+        "-dev"
+    } else {
+        ""
+    }
+}
+
+/// Builds a four component version string from the given components, where all components after
+/// the given (1-based) position are reset to zero, and the one at the position is incremented.
+fn manipulate_version(components: &[Option<&str>; 4], position: usize, increment: u64) -> String {
+    components
+        .iter()
+        .enumerate()
+        .map(|(i, component)| match (i + 1).cmp(&position) {
+            Ordering::Greater => "0".to_string(),
+            Ordering::Equal if increment > 0 => component
+                .and_then(|c| c.parse::<u64>().ok())
+                .unwrap_or_default()
+                .saturating_add(increment)
+                .to_string(),
+            _ => component.unwrap_or_default().to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // match all
+    #[case("*", "*")]
+    #[case("*.*", ">= 0.0.0.0-dev")]
+    #[case("v*.*", ">= 0.0.0.0-dev")]
+    #[case("x.X.x.*", ">= 0.0.0.0-dev")]
+    // basic comparators
+    #[case("<>1.0.0", "!= 1.0.0.0")]
+    #[case("!=1.0.0", "!= 1.0.0.0")]
+    #[case(">1.0.0", "> 1.0.0.0")]
+    #[case("<1.2.3.4", "< 1.2.3.4-dev")]
+    #[case("<=1.2.3", "<= 1.2.3.0")]
+    #[case(">=1.2.3", ">= 1.2.3.0-dev")]
+    #[case("=1.2.3", "== 1.2.3.0")]
+    #[case("==1.2.3", "== 1.2.3.0")]
+    #[case("1.2.3", "== 1.2.3.0")]
+    #[case("=1.0", "== 1.0.0.0")]
+    #[case("1.2.3b5", "== 1.2.3.0-beta5")]
+    #[case("1.2.3a1", "== 1.2.3.0-alpha1")]
+    #[case("1.2.3p1234", "== 1.2.3.0-patch1234")]
+    #[case("1.2.3pl1234", "== 1.2.3.0-patch1234")]
+    #[case(">= 1.2.3", ">= 1.2.3.0-dev")]
+    #[case("< 1.2.3", "< 1.2.3.0-dev")]
+    #[case("> 1.2.3", "> 1.2.3.0")]
+    #[case(">=dev-master", ">= dev-master")]
+    #[case("dev-master", "== dev-master")]
+    #[case("dev-feature-a", "== dev-feature-a")]
+    #[case("dev-some-fix", "== dev-some-fix")]
+    #[case("dev-CAPS", "== dev-CAPS")]
+    #[case("dev-master as 1.0.0", "== dev-master")]
+    #[case("<1.2.3.4-stable", "< 1.2.3.4")]
+    #[case(">=1.2.3.4-stable", ">= 1.2.3.4")]
+    #[case("foobar-dev", "== dev-foobar")]
+    // stability flags and references
+    #[case("1.0@dev", "== 1.0.0.0")]
+    #[case(">=1.0@dev", ">= 1.0.0.0-dev")]
+    #[case(">1.0@beta", "> 1.0.0.0-beta")]
+    #[case("@dev", "*")]
+    #[case("1.0.x-dev#abcd123", "== 1.0.9999999.9999999-dev")]
+    #[case("dev-master#abcd123", "== dev-master")]
+    // wildcards
+    #[case("v2.*", "[>= 2.0.0.0-dev < 3.0.0.0-dev]")]
+    #[case("2.*.*", "[>= 2.0.0.0-dev < 3.0.0.0-dev]")]
+    #[case("20.*", "[>= 20.0.0.0-dev < 21.0.0.0-dev]")]
+    #[case("20.*.*", "[>= 20.0.0.0-dev < 21.0.0.0-dev]")]
+    #[case("2.0.*", "[>= 2.0.0.0-dev < 2.1.0.0-dev]")]
+    #[case("2.x", "[>= 2.0.0.0-dev < 3.0.0.0-dev]")]
+    #[case("2.x.x", "[>= 2.0.0.0-dev < 3.0.0.0-dev]")]
+    #[case("2.2.x", "[>= 2.2.0.0-dev < 2.3.0.0-dev]")]
+    #[case("2.10.X", "[>= 2.10.0.0-dev < 2.11.0.0-dev]")]
+    #[case("2.1.3.*", "[>= 2.1.3.0-dev < 2.1.4.0-dev]")]
+    #[case("0.*", "< 1.0.0.0-dev")]
+    #[case("0.*.*", "< 1.0.0.0-dev")]
+    #[case("0.x", "< 1.0.0.0-dev")]
+    #[case("0.x.x", "< 1.0.0.0-dev")]
+    // tilde ranges
+    #[case("~v1", "[>= 1.0.0.0-dev < 2.0.0.0-dev]")]
+    #[case("~1.0", "[>= 1.0.0.0-dev < 2.0.0.0-dev]")]
+    #[case("~1.0.0", "[>= 1.0.0.0-dev < 1.1.0.0-dev]")]
+    #[case("~1.2", "[>= 1.2.0.0-dev < 2.0.0.0-dev]")]
+    #[case("~1.2.3", "[>= 1.2.3.0-dev < 1.3.0.0-dev]")]
+    #[case("~1.2.3.4", "[>= 1.2.3.4-dev < 1.2.4.0-dev]")]
+    #[case("~1.2-beta", "[>= 1.2.0.0-beta < 2.0.0.0-dev]")]
+    #[case("~1.2-b2", "[>= 1.2.0.0-beta2 < 2.0.0.0-dev]")]
+    #[case("~1.2-BETA2", "[>= 1.2.0.0-beta2 < 2.0.0.0-dev]")]
+    #[case("~1.2.2-dev", "[>= 1.2.2.0-dev < 1.3.0.0-dev]")]
+    #[case("~1.2.2-stable", "[>= 1.2.2.0 < 1.3.0.0-dev]")]
+    // caret ranges
+    #[case("^v1", "[>= 1.0.0.0-dev < 2.0.0.0-dev]")]
+    #[case("^0", "[>= 0.0.0.0-dev < 1.0.0.0-dev]")]
+    #[case("^0.0", "[>= 0.0.0.0-dev < 0.1.0.0-dev]")]
+    #[case("^1.2", "[>= 1.2.0.0-dev < 2.0.0.0-dev]")]
+    #[case("^1.2.3-beta.2", "[>= 1.2.3.0-beta2 < 2.0.0.0-dev]")]
+    #[case("^1.2.3.4", "[>= 1.2.3.4-dev < 2.0.0.0-dev]")]
+    #[case("^1.2.3", "[>= 1.2.3.0-dev < 2.0.0.0-dev]")]
+    #[case("^0.2.3", "[>= 0.2.3.0-dev < 0.3.0.0-dev]")]
+    #[case("^0.2", "[>= 0.2.0.0-dev < 0.3.0.0-dev]")]
+    #[case("^0.2.0", "[>= 0.2.0.0-dev < 0.3.0.0-dev]")]
+    #[case("^0.0.3", "[>= 0.0.3.0-dev < 0.0.4.0-dev]")]
+    #[case("^0.0.3-alpha", "[>= 0.0.3.0-alpha < 0.0.4.0-dev]")]
+    #[case("^0.0.3-dev", "[>= 0.0.3.0-dev < 0.0.4.0-dev]")]
+    // hyphen ranges
+    #[case("1 - 2", "[>= 1.0.0.0-dev < 3.0.0.0-dev]")]
+    #[case("1.2.3 - 2.3.4.5", "[>= 1.2.3.0-dev <= 2.3.4.5]")]
+    #[case("1.2-beta - 2.3", "[>= 1.2.0.0-beta < 2.4.0.0-dev]")]
+    #[case("1.2-beta - 2.3-dev", "[>= 1.2.0.0-beta <= 2.3.0.0-dev]")]
+    #[case("1.2-RC - 2.3.1", "[>= 1.2.0.0-RC <= 2.3.1.0]")]
+    #[case("1.2.3-alpha - 2.3-RC", "[>= 1.2.3.0-alpha <= 2.3.0.0-RC]")]
+    #[case("1 - 2.0", "[>= 1.0.0.0-dev < 2.1.0.0-dev]")]
+    #[case("1 - 2.1", "[>= 1.0.0.0-dev < 2.2.0.0-dev]")]
+    #[case("1.2 - 2.1.0", "[>= 1.2.0.0-dev <= 2.1.0.0]")]
+    #[case("1.3 - 2.1.3", "[>= 1.3.0.0-dev <= 2.1.3.0]")]
+    // multiple constraints
+    #[case(">2.0,<=3.0", "[> 2.0.0.0 <= 3.0.0.0]")]
+    #[case(">2.0 <=3.0", "[> 2.0.0.0 <= 3.0.0.0]")]
+    #[case(">2.0  <=3.0", "[> 2.0.0.0 <= 3.0.0.0]")]
+    #[case(">2.0, <=3.0", "[> 2.0.0.0 <= 3.0.0.0]")]
+    #[case(">2.0 ,<=3.0", "[> 2.0.0.0 <= 3.0.0.0]")]
+    #[case(">2.0 , <=3.0", "[> 2.0.0.0 <= 3.0.0.0]")]
+    #[case("> 2.0   <=  3.0", "[> 2.0.0.0 <= 3.0.0.0]")]
+    #[case(">= 2.7.0.0-dev , < 2.8.0.0-dev", "[>= 2.7.0.0-dev < 2.8.0.0-dev]")]
+    #[case(
+        "^1.0 1.2.*",
+        "[>= 1.0.0.0-dev < 2.0.0.0-dev >= 1.2.0.0-dev < 1.3.0.0-dev]"
+    )]
+    #[case("<1.1 || >1.3", "[< 1.1.0.0-dev || > 1.3.0.0]")]
+    #[case("<1.1 | >1.3", "[< 1.1.0.0-dev || > 1.3.0.0]")]
+    #[case(
+        "^1.0, <1.5 || ^3.0",
+        "[[>= 1.0.0.0-dev < 2.0.0.0-dev < 1.5.0.0-dev] || [>= 3.0.0.0-dev < 4.0.0.0-dev]]"
+    )]
+    #[case(
+        "dev-master as 1.0.x-dev || ^2.0",
+        "[== dev-master || [>= 2.0.0.0-dev < 3.0.0.0-dev]]"
+    )]
+    #[case(
+        "1.0.0 - 2.0.0 || ^3",
+        "[[>= 1.0.0.0-dev <= 2.0.0.0] || [>= 3.0.0.0-dev < 4.0.0.0-dev]]"
+    )]
+    fn test_parse_constraints(#[case] constraint: &str, #[case] expected: &str) {
+        let parsed: ComposerVersionConstraint = constraint.parse().unwrap();
+        assert_eq!(parsed.constraint().to_string(), expected);
+        assert_eq!(parsed.to_string(), constraint);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("1.0.0-meh")]
+    #[case(">2.0,,<=3.0")]
+    #[case(">2.0 ,, <=3.0")]
+    #[case(">2.0 ||| <=3.0")]
+    #[case("~>1.2")]
+    #[case("~1.2 ||")]
+    #[case("|| ^1")]
+    #[case("1.0.0-dev<1.0.5-dev")]
+    #[case("^")]
+    #[case("^8 || ^")]
+    #[case("~")]
+    #[case("~1 ~")]
+    #[case("1.0 .2")]
+    fn test_parse_constraints_failure(#[case] constraint: &str) {
+        assert!(constraint.parse::<ComposerVersionConstraint>().is_err());
+    }
+
+    #[rstest]
+    #[case("^8.1", "8.1.0", true)]
+    #[case("^8.1", "8.4.21", true)]
+    #[case("^8.1", "9.0.0", false)]
+    #[case("^8.1", "8.0.30", false)]
+    // stability is not a concern of constraints, but lower bounds include pre-releases
+    #[case("^8.1", "8.1.0RC1", true)]
+    #[case(">8.1", "8.1.0RC2", false)]
+    #[case(">8.1@RC", "8.1.0RC2", true)]
+    #[case("~8.3.0", "8.3.30", true)]
+    #[case("~8.3.0", "8.4.0", false)]
+    #[case("8.3.*", "8.3.30+heroku1", true)]
+    #[case("8.3.*", "8.30.1", false)]
+    #[case("<8.4", "8.4.0-beta1", false)]
+    #[case("<8.4", "8.3.99", true)]
+    #[case(">=8.2 <8.4 || ^8.5", "8.4.1", false)]
+    #[case(">=8.2 <8.4 || ^8.5", "8.5.0", true)]
+    #[case("8.2.1 - 8.3", "8.3.15", true)]
+    #[case("8.2.1 - 8.3", "8.4.0", false)]
+    #[case("!=8.3.1", "8.3.1", false)]
+    #[case("!=8.3.1", "8.3.10", true)]
+    #[case("*", "dev-main", true)]
+    #[case("*.*", "dev-main", false)]
+    #[case("dev-main", "dev-main", true)]
+    #[case("dev-main", "1.0.0", false)]
+    #[case(">=1.0", "dev-main", false)]
+    #[case("1.x-dev", "1.x-dev", true)]
+    #[case("^1.0", "1.x-dev", true)]
+    fn test_matches(#[case] constraint: &str, #[case] version: &str, #[case] expected: bool) {
+        let parsed: ComposerVersionConstraint = constraint.parse().unwrap();
+        assert_eq!(parsed.matches(&version.parse().unwrap()), expected);
+    }
+}
@@ -0,0 +1,244 @@
+//! The content hash of a `composer.json`, as recorded in the `content-hash` field of its lock file.
+//!
+//! Mirrors `Composer\Package\Locker::getContentHash`.
+
+use crate::ComposerRootPackage;
+use md5::{Digest, Md5};
+use serde_json::{Map, Value, json};
+use std::fmt::Write;
+
+/// The top-level keys of a `composer.json` that are relevant for its lock file.
+const RELEVANT_KEYS: [&str; 11] = [
+    "name",
+    "version",
+    "require",
+    "require-dev",
+    "conflict",
+    "replace",
+    "provide",
+    "minimum-stability",
+    "prefer-stable",
+    "repositories",
+    "extra",
+];
+
+/// Computes the hash of the parts of the given `composer.json` document that are relevant for its lock file.
+///
+/// A lock file is up to date with its `composer.json` if its `content-hash` is this value.
+///
+/// The hash is computed from the document as it was decoded, not from a [`ComposerRootPackage`](crate::ComposerRootPackage),
+/// since any normalization (like of a `minimum-stability` of `rc` to `RC`) would change the hash.
+#[must_use]
+pub fn composer_json_content_hash(composer_json: &Value) -> String {
+    let mut relevant = RELEVANT_KEYS
+        .iter()
+        .filter_map(|key| Some((key.to_string(), composer_json.get(*key)?.clone())))
+        .collect::<Map<_, _>>();
+    if let Some(platform) = composer_json
+        .get("config")
+        .and_then(|config| config.get("platform"))
+        .filter(|platform| !platform.is_null())
+    {
+        relevant.insert("config".to_string(), json!({ "platform": platform }));
+    }
+    relevant.sort_keys();
+
+    let mut encoded = String::new();
+    php_json_encode(&Value::Object(relevant), &mut encoded);
+    format!("{:x}", Md5::digest(encoded.as_bytes()))
+}
+
+impl ComposerRootPackage {
+    /// The [content hash](composer_json_content_hash) of the document this package was deserialized from.
+    ///
+    /// Changes made to the package after deserialization are not reflected. Returns `None` if the
+    /// package was not deserialized, as the hash cannot be computed from its normalized values.
+    #[must_use]
+    pub fn content_hash(&self) -> Option<String> {
+        self.key_order.0.as_ref().map(composer_json_content_hash)
+    }
+}
+
+/// Encodes the given value like PHP's `json_encode()` without any flags does for the result of
+/// `json_decode()` into associative arrays.
+///
+/// This means that slashes and non-ASCII characters are escaped, and that objects that PHP cannot
+/// tell apart from lists (empty ones, or those with keys `0`, `1`, …) are encoded as lists.
+fn php_json_encode(value: &Value, encoded: &mut String) {
+    match value {
+        Value::Null => encoded.push_str("null"),
+        Value::Bool(value) => encoded.push_str(if *value { "true" } else { "false" }),
+        Value::Number(number) => match number.as_f64() {
+            // PHP keeps a fractional part for floats; large or tiny ones may still differ in notation
+            Some(float) if number.is_f64() && float.fract() == 0.0 && float.abs() < 1e15 => {
+                let _ = write!(encoded, "{float:.1}");
+            }
+            _ => {
+                let _ = write!(encoded, "{number}");
+            }
+        },
+        Value::String(string) => php_json_encode_string(string, encoded),
+        Value::Array(values) => php_json_encode_list(values.iter(), encoded),
+        Value::Object(object)
+            if object
+                .keys()
+                .enumerate()
+                .all(|(index, key)| *key == index.to_string()) =>
+        {
+            php_json_encode_list(object.values(), encoded);
+        }
+        Value::Object(object) => {
+            encoded.push('{');
+            for (index, (key, value)) in object.iter().enumerate() {
+                if index > 0 {
+                    encoded.push(',');
+                }
+                php_json_encode_string(key, encoded);
+                encoded.push(':');
+                php_json_encode(value, encoded);
+            }
+            encoded.push('}');
+        }
+    }
+}
+
+fn php_json_encode_list<'a>(values: impl Iterator<Item = &'a Value>, encoded: &mut String) {
+    encoded.push('[');
+    for (index, value) in values.enumerate() {
+        if index > 0 {
+            encoded.push(',');
+        }
+        php_json_encode(value, encoded);
+    }
+    encoded.push(']');
+}
+
+fn php_json_encode_string(string: &str, encoded: &mut String) {
+    encoded.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            '/' => encoded.push_str("\\/"),
+            '\u{8}' => encoded.push_str("\\b"),
+            '\u{c}' => encoded.push_str("\\f"),
+            '\n' => encoded.push_str("\\n"),
+            '\r' => encoded.push_str("\\r"),
+            '\t' => encoded.push_str("\\t"),
+            c if c < ' ' || !c.is_ascii() => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    let _ = write!(encoded, "\\u{unit:04x}");
+                }
+            }
+            c => encoded.push(c),
+        }
+    }
+    encoded.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ComposerLock;
+    use rstest::rstest;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn md5_of_encoded(value: &Value) -> String {
+        let mut encoded = String::new();
+        php_json_encode(value, &mut encoded);
+        format!("{:x}", Md5::digest(encoded.as_bytes()))
+    }
+
+    #[rstest]
+    fn test_content_hash(#[files("tests/fixtures/*.lock")] path: PathBuf) {
+        let lock = serde_json::from_slice::<ComposerLock>(&fs::read(&path).unwrap()).unwrap();
+        let composer_json = fs::read(path.with_extension("json")).unwrap();
+        assert_eq!(
+            composer_json_content_hash(&serde_json::from_slice(&composer_json).unwrap()),
+            lock.content_hash
+        );
+        assert_eq!(
+            serde_json::from_slice::<ComposerRootPackage>(&composer_json)
+                .unwrap()
+                .content_hash(),
+            Some(lock.content_hash)
+        );
+    }
+
+    #[test]
+    fn test_root_package_content_hash() {
+        // the hash of the document is kept, even though the value is normalized
+        let package =
+            serde_json::from_value::<ComposerRootPackage>(json!({"minimum-stability": "rc"}))
+                .unwrap();
+        assert_eq!(
+            package.content_hash(),
+            Some(composer_json_content_hash(
+                &json!({"minimum-stability": "rc"})
+            ))
+        );
+        assert_eq!(ComposerRootPackage::default().content_hash(), None);
+    }
+
+    #[test]
+    fn test_content_hash_relevant_keys() {
+        // irrelevant keys, and config other than platform, do not change the hash
+        assert_eq!(
+            composer_json_content_hash(&json!({"require": {"php": "^8.3"}})),
+            composer_json_content_hash(&json!({
+                "description": "Something",
+                "require": {"php": "^8.3"},
+                "config": {"optimize-autoloader": true},
+                "scripts": {"test": "phpunit"},
+            }))
+        );
+        assert_ne!(
+            composer_json_content_hash(&json!({"require": {"php": "^8.3"}})),
+            composer_json_content_hash(
+                &json!({"require": {"php": "^8.3"}, "config": {"platform": {"php": "8.3.0"}}})
+            )
+        );
+        // the order of top-level keys does not matter, but that of other keys does
+        assert_eq!(
+            composer_json_content_hash(&json!({"require": {"php": "*"}, "name": "acme/app"})),
+            composer_json_content_hash(&json!({"name": "acme/app", "require": {"php": "*"}}))
+        );
+        assert_ne!(
+            composer_json_content_hash(&json!({"minimum-stability": "rc"})),
+            composer_json_content_hash(&json!({"minimum-stability": "RC"}))
+        );
+        assert_ne!(
+            composer_json_content_hash(&json!({"extra": {"a": 1, "b": 2}})),
+            composer_json_content_hash(&json!({"extra": {"b": 2, "a": 1}}))
+        );
+    }
+
+    #[rstest]
+    // values are hashed exactly as written, without any normalization
+    #[case(json!({"minimum-stability": "rc"}))]
+    #[case(json!({"minimum-stability": "RC", "prefer-stable": false}))]
+    #[case(json!({"repositories": {"acme": {"url": "https://packages.acme.com", "type": "composer"}}}))]
+    #[case(json!({"repositories": [{"packagist.org": false}]}))]
+    #[case(json!({"name": null, "require": []}))]
+    fn test_content_hash_raw(#[case] composer_json: Value) {
+        assert_eq!(
+            composer_json_content_hash(&composer_json),
+            md5_of_encoded(&composer_json)
+        );
+    }
+
+    #[rstest]
+    #[case(json!({}), "[]")]
+    #[case(json!({"0": "a", "1": "b"}), r#"["a","b"]"#)]
+    #[case(json!({"1": "a", "0": "b"}), r#"{"1":"a","0":"b"}"#)]
+    #[case(json!({"url": "https://example.com/a b"}), r#"{"url":"https:\/\/example.com\/a b"}"#)]
+    #[case(json!("Ümlaut 🐘"), r#""\u00dcmlaut \ud83d\udc18""#)]
+    #[case(json!("\"quoted\"\t\\\u{1}"), r#""\"quoted\"\t\\\u0001""#)]
+    #[case(json!([1, 1.0, 1.5, -2, null, true, false]), "[1,1.0,1.5,-2,null,true,false]")]
+    fn test_php_json_encode(#[case] value: Value, #[case] expected: &str) {
+        let mut encoded = String::new();
+        php_json_encode(&value, &mut encoded);
+        assert_eq!(encoded, expected);
+    }
+}
@@ -0,0 +1,122 @@
+//! Heroku specific settings, as defined in the `extra.heroku` section of a root package.
+
+use crate::ComposerRootPackage;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// The `extra.heroku` section of a root package.
+///
+/// File paths are relative to the project directory.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ComposerHerokuExtra {
+    /// Version constraints for web servers (`apache`, `nginx`), by name
+    pub engines: IndexMap<String, String>,
+    /// An Apache config file to include in the app's `VirtualHost`
+    pub apache_includes: Vec<String>,
+    /// An Nginx config file to include in the app's `server` block
+    pub nginx_includes: Vec<String>,
+    /// A PHP-FPM config file to include in the app's pool
+    pub fpm_config: Option<String>,
+    /// PHP INI directives (like `short_open_tag=off`) applied to all PHP processes
+    pub php_config: Vec<String>,
+    /// Whether to enable `opcache.preload` with a conventional script, or the path to the script to use
+    pub opcache_preload: Option<ComposerHerokuOpcachePreload>,
+}
+
+/// The `extra.heroku.opcache-preload` setting, either `true`/`false` or the path to a preload script.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ComposerHerokuOpcachePreload {
+    Enabled(bool),
+    Script(String),
+}
+
+impl ComposerRootPackage {
+    /// The [`ComposerHerokuExtra`] settings of this package; all defaults if there are none.
+    ///
+    /// The settings are read from `extra` on each call, so `extra` itself is retained as-is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `extra.heroku` does not match the structure of [`ComposerHerokuExtra`].
+    pub fn heroku_extra(&self) -> Result<ComposerHerokuExtra, serde_json::Error> {
+        self.package
+            .extra
+            .as_ref()
+            .and_then(|extra| extra.get("heroku"))
+            .map_or_else(
+                || Ok(ComposerHerokuExtra::default()),
+                ComposerHerokuExtra::deserialize,
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn heroku_extra(composer_json: Value) -> Result<ComposerHerokuExtra, serde_json::Error> {
+        serde_json::from_value::<ComposerRootPackage>(composer_json)
+            .unwrap()
+            .heroku_extra()
+    }
+
+    #[test]
+    fn test_heroku_extra() {
+        assert_eq!(
+            heroku_extra(json!({})).unwrap(),
+            ComposerHerokuExtra::default()
+        );
+        assert_eq!(
+            heroku_extra(json!({"extra": {"branch-alias": {"dev-main": "1.x-dev"}}})).unwrap(),
+            ComposerHerokuExtra::default()
+        );
+
+        let extra = heroku_extra(json!({"extra": {"heroku": {
+            "engines": {"nginx": "^1.28", "php": "^8.3"},
+            "nginx-includes": ["nginx.conf"],
+            "fpm-config": "fpm.conf",
+            "php-config": ["short_open_tag=off", "memory_limit=256M"],
+            "opcache-preload": "config/preload.php",
+            "unknown-setting": true,
+        }}}))
+        .unwrap();
+        assert_eq!(
+            extra.engines,
+            IndexMap::from([
+                ("nginx".to_string(), "^1.28".to_string()),
+                ("php".to_string(), "^8.3".to_string()),
+            ])
+        );
+        assert_eq!(extra.nginx_includes, vec!["nginx.conf"]);
+        assert!(extra.apache_includes.is_empty());
+        assert_eq!(extra.fpm_config.as_deref(), Some("fpm.conf"));
+        assert_eq!(
+            extra.php_config,
+            vec!["short_open_tag=off", "memory_limit=256M"]
+        );
+        assert_eq!(
+            extra.opcache_preload,
+            Some(ComposerHerokuOpcachePreload::Script(
+                "config/preload.php".to_string()
+            ))
+        );
+        assert_eq!(
+            heroku_extra(json!({"extra": {"heroku": {"opcache-preload": true}}}))
+                .unwrap()
+                .opcache_preload,
+            Some(ComposerHerokuOpcachePreload::Enabled(true))
+        );
+
+        for invalid in [
+            json!({"php-config": "short_open_tag=off"}),
+            json!({"php-config": [true]}),
+            json!({"engines": {"nginx": 1}}),
+            json!({"opcache-preload": ["preload.php"]}),
+        ] {
+            assert!(heroku_extra(json!({"extra": {"heroku": invalid}})).is_err());
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! The record of installed packages that Composer keeps in `vendor/composer/installed.json`.
+//!
+//! Composer also writes this information to `vendor/composer/installed.php` for use at runtime,
+//! but reading that file requires PHP.
+
+use crate::{ComposerLock, ComposerPackage};
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_with::skip_serializing_none;
+use std::fmt;
+use std::path::PathBuf;
+
+/// The packages installed into a vendor directory.
+///
+/// Deserializes from the format of Composer 1 (a list of packages) as well as that of Composer 2
+/// (an object with `packages`, `dev` and `dev-package-names`); serializes to the latter.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ComposerInstalledRepository {
+    pub packages: Vec<ComposerInstalledPackage>,
+    /// Whether dev requirements were installed; not recorded by Composer 1
+    pub dev: Option<bool>,
+    /// The names of the installed packages that are only required for development; not recorded by Composer 1
+    pub dev_package_names: Option<Vec<String>>,
+}
+
+impl ComposerInstalledRepository {
+    /// The installed package with the given name, if any.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&ComposerInstalledPackage> {
+        self.packages
+            .iter()
+            .find(|installed| installed.package.name == name)
+    }
+
+    /// Whether the installed package with the given name is only required for development.
+    ///
+    /// Returns `None` if this is not recorded (by Composer 1).
+    #[must_use]
+    pub fn is_dev_package(&self, name: &str) -> Option<bool> {
+        self.dev_package_names
+            .as_ref()
+            .map(|names| names.iter().any(|dev_name| dev_name == name))
+    }
+
+    /// The packages from the given lock file that are not installed in their locked version.
+    ///
+    /// The lock file's dev packages are only checked if `dev` is set.
+    #[must_use]
+    pub fn missing_locked_packages<'a>(
+        &self,
+        lock: &'a ComposerLock,
+        dev: bool,
+    ) -> Vec<&'a ComposerPackage> {
+        lock.packages
+            .iter()
+            .chain(lock.packages_dev.iter().filter(|_| dev))
+            .filter(|locked| {
+                self.get(&locked.name)
+                    .is_none_or(|installed| installed.package.version != locked.version)
+            })
+            .collect()
+    }
+}
+
+impl<'de> Deserialize<'de> for ComposerInstalledRepository {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ComposerInstalledRepositoryVisitor;
+
+        impl<'de> Visitor<'de> for ComposerInstalledRepositoryVisitor {
+            type Value = ComposerInstalledRepository;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a list of installed packages (Composer 1), or an object with a list of installed packages in key 'packages' (Composer 2)")
+            }
+
+            fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                Ok(ComposerInstalledRepository {
+                    packages: Vec::deserialize(SeqAccessDeserializer::new(seq))?,
+                    dev: None,
+                    dev_package_names: None,
+                })
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                #[derive(Deserialize)]
+                #[serde(rename_all = "kebab-case")]
+                struct Composer2Format {
+                    packages: Vec<ComposerInstalledPackage>,
+                    dev: Option<bool>,
+                    dev_package_names: Option<Vec<String>>,
+                }
+
+                let installed = Composer2Format::deserialize(MapAccessDeserializer::new(map))?;
+                Ok(ComposerInstalledRepository {
+                    packages: installed.packages,
+                    dev: installed.dev,
+                    dev_package_names: installed.dev_package_names,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(ComposerInstalledRepositoryVisitor)
+    }
+}
+
+/// An installed package.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ComposerInstalledPackage {
+    #[serde(flatten)]
+    pub package: ComposerPackage,
+    /// The installation directory, relative to `vendor/composer/`; not recorded by Composer 1
+    pub install_path: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::lock_with;
+    use serde_json::json;
+
+    #[test]
+    fn test_composer1() {
+        let installed = serde_json::from_value::<ComposerInstalledRepository>(json!([
+            {"name": "monolog/monolog", "version": "1.27.1", "version_normalized": "1.27.1.0", "type": "library"},
+            {"name": "psr/log", "version": "1.1.4", "type": "library"},
+        ]))
+        .unwrap();
+        assert_eq!(installed.packages.len(), 2);
+        assert_eq!(installed.dev, None);
+        assert_eq!(installed.is_dev_package("psr/log"), None);
+        let monolog = installed.get("monolog/monolog").unwrap();
+        assert_eq!(monolog.package.version, "1.27.1");
+        assert_eq!(monolog.install_path, None);
+        assert!(
+            monolog
+                .package
+                .package
+                .extra_fields
+                .contains_key("version_normalized")
+        );
+    }
+
+    #[test]
+    fn test_composer2() {
+        let input = json!({
+            "packages": [
+                {"name": "monolog/monolog", "version": "3.7.0", "type": "library", "install-path": "../monolog/monolog"},
+                {"name": "phpunit/phpunit", "version": "11.3.6", "type": "library", "install-path": "../phpunit/phpunit"},
+            ],
+            "dev": true,
+            "dev-package-names": ["phpunit/phpunit"],
+        });
+        let installed =
+            serde_json::from_value::<ComposerInstalledRepository>(input.clone()).unwrap();
+        assert_eq!(installed.dev, Some(true));
+        assert_eq!(installed.is_dev_package("monolog/monolog"), Some(false));
+        assert_eq!(installed.is_dev_package("phpunit/phpunit"), Some(true));
+        assert_eq!(
+            installed.get("monolog/monolog").unwrap().install_path,
+            Some(PathBuf::from("../monolog/monolog"))
+        );
+        assert!(
+            !installed
+                .get("monolog/monolog")
+                .unwrap()
+                .package
+                .package
+                .extra_fields
+                .contains_key("install-path")
+        );
+        assert_eq!(serde_json::to_value(&installed).unwrap(), input);
+
+        assert!(serde_json::from_value::<ComposerInstalledRepository>(json!({})).is_err());
+        assert!(serde_json::from_value::<ComposerInstalledRepository>(json!("nope")).is_err());
+    }
+
+    #[test]
+    fn test_missing_locked_packages() {
+        let lock = lock_with(json!({
+            "packages": [
+                {"name": "monolog/monolog", "version": "3.7.0"},
+                {"name": "psr/log", "version": "3.0.2"},
+            ],
+            "packages-dev": [{"name": "phpunit/phpunit", "version": "11.3.6"}],
+        }));
+        let installed = serde_json::from_value::<ComposerInstalledRepository>(json!({
+            "packages": [
+                {"name": "monolog/monolog", "version": "3.7.0"},
+                {"name": "psr/log", "version": "3.0.1"},
+            ],
+            "dev": false,
+            "dev-package-names": [],
+        }))
+        .unwrap();
+
+        let names = |packages: Vec<&ComposerPackage>| {
+            packages
+                .into_iter()
+                .map(|package| package.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(installed.missing_locked_packages(&lock, false)),
+            vec!["psr/log"]
+        );
+        assert_eq!(
+            names(installed.missing_locked_packages(&lock, true)),
+            vec!["psr/log", "phpunit/phpunit"]
+        );
+    }
+}
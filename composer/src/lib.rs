@@ -3,7 +3,7 @@ use git_url_parse::GitUrl;
 use indexmap::IndexMap;
 use monostate::MustBe;
 use serde::de::{Error, MapAccess, SeqAccess, Visitor};
-use serde::ser::{SerializeMap, Serializer};
+use serde::ser::{SerializeMap, SerializeSeq, Serializer};
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use serde_with::{OneOrMany, TryFromInto, formats::PreferOne, serde_as, skip_serializing_none};
@@ -12,8 +12,74 @@ use std::marker::PhantomData;
 use std::path::PathBuf;
 use url::Url;
 
-#[derive(Clone, Debug, Default, Deref, From, PartialEq, Serialize)]
-pub struct PhpAssocArray<T>(IndexMap<String, T>);
+macro_rules! regex {
+    ($re:literal $(,)?) => {{
+        static RE: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+        RE.get_or_init(|| {
+            regex::Regex::new($re).expect("Internal error: failed to compile regular expression.")
+        })
+    }};
+}
+
+mod constraint;
+mod content_hash;
+mod heroku;
+mod installed;
+mod scripts;
+mod validation;
+mod version;
+
+pub use constraint::{
+    ComposerConstraint, ComposerConstraintOperator, ComposerVersionConstraint,
+    ComposerVersionConstraintError,
+};
+pub use content_hash::composer_json_content_hash;
+pub use heroku::{ComposerHerokuExtra, ComposerHerokuOpcachePreload};
+pub use installed::{ComposerInstalledPackage, ComposerInstalledRepository};
+pub use scripts::{
+    ComposerScript, ComposerScriptCommand, ComposerScriptExpansionError, ComposerScripts,
+};
+pub use validation::{ComposerValidationError, check_package_names, validate_composer_json};
+pub use version::{ComposerVersion, ComposerVersionError};
+
+/// An object that PHP may have encoded as an empty array (`[]`) if it had no entries.
+///
+/// The notation of an empty value is retained when serialized.
+#[derive(Clone, Debug, Default, Deref)]
+pub struct PhpAssocArray<T> {
+    #[deref]
+    entries: IndexMap<String, T>,
+    empty_array_notation: bool,
+}
+
+impl<T> From<IndexMap<String, T>> for PhpAssocArray<T> {
+    fn from(entries: IndexMap<String, T>) -> Self {
+        Self {
+            entries,
+            empty_array_notation: false,
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for PhpAssocArray<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<T: Serialize> Serialize for PhpAssocArray<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.entries.is_empty() && self.empty_array_notation {
+            serializer.serialize_seq(Some(0))?.end()
+        } else {
+            self.entries.serialize(serializer)
+        }
+    }
+}
+
 impl<'de, T: Deserialize<'de> + Default> Deserialize<'de> for PhpAssocArray<T> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -39,7 +105,10 @@ impl<'de, T: Deserialize<'de> + Default> Deserialize<'de> for PhpAssocArray<T> {
             {
                 match seq.next_element::<T>() {
                     Ok(Some(_)) | Err(_) => Err(Error::custom("sequence must be empty")), // if a T is in there or something else is in there
-                    Ok(None) => Ok(PhpAssocArray::default()),
+                    Ok(None) => Ok(PhpAssocArray {
+                        entries: IndexMap::new(),
+                        empty_array_notation: true,
+                    }),
                 }
             }
 
@@ -52,7 +121,7 @@ impl<'de, T: Deserialize<'de> + Default> Deserialize<'de> for PhpAssocArray<T> {
                     values.insert(key, value);
                 }
 
-                Ok(PhpAssocArray(values))
+                Ok(PhpAssocArray::from(values))
             }
         }
 
@@ -136,19 +205,92 @@ impl<T> SingleEntryKVMap<T> {
     }
 }
 
+/// The order of keys in the document a value was deserialized from.
+///
+/// Serialization emits fields in declaration order; this allows restoring the original order instead.
+#[derive(Clone, Debug, Default)]
+pub struct ComposerKeyOrder(Option<Value>);
+
+impl ComposerKeyOrder {
+    /// Orders the keys of all objects in the given value like those in the original document.
+    fn apply(&self, value: &mut Value) {
+        if let Some(original) = &self.0 {
+            order_keys_like(value, original);
+        }
+    }
+}
+
+/// Recursively orders the keys of all objects in the given value like those of the given original;
+/// keys that the original does not have come last, in their existing order.
+fn order_keys_like(value: &mut Value, original: &Value) {
+    match (value, original) {
+        (Value::Object(object), Value::Object(original)) => {
+            let mut rest = std::mem::take(object);
+            for (key, original_value) in original {
+                if let Some((key, mut value)) = rest.shift_remove_entry(key) {
+                    order_keys_like(&mut value, original_value);
+                    object.insert(key, value);
+                }
+            }
+            object.extend(rest);
+        }
+        (Value::Array(values), Value::Array(original)) => {
+            for (value, original_value) in values.iter_mut().zip(original) {
+                order_keys_like(value, original_value);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Implements [`Deserialize`] and [`Serialize`] for a type that derives them with `#[serde(remote = "Self")]`,
+/// retaining the key order of the deserialized document in the type's `key_order` field.
+macro_rules! impl_serde_with_key_order {
+    ($type:ty) => {
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let document = Value::deserialize(deserializer)?;
+                let mut value = <$type>::deserialize(&document).map_err(Error::custom)?;
+                value.key_order = ComposerKeyOrder(Some(document));
+                Ok(value)
+            }
+        }
+
+        impl Serialize for $type {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let mut value = <$type>::serialize(self, serde_json::value::Serializer)
+                    .map_err(serde::ser::Error::custom)?;
+                self.key_order.apply(&mut value);
+                value.serialize(serializer)
+            }
+        }
+    };
+}
+
 #[skip_serializing_none]
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
-#[serde(rename_all = "kebab-case")]
+#[serde(remote = "Self", rename_all = "kebab-case")]
 pub struct ComposerRootPackage {
     pub name: Option<String>,
     pub version: Option<String>,
-    pub config: Option<IndexMap<String, Value>>,
+    pub config: Option<ComposerConfig>,
     pub minimum_stability: Option<ComposerStability>,
     pub prefer_stable: Option<bool>,
     #[serde(flatten)]
     pub package: ComposerBasePackage,
+    /// The key order of the deserialized document, which serialization retains
+    #[serde(skip)]
+    pub key_order: ComposerKeyOrder,
 }
 
+impl_serde_with_key_order!(ComposerRootPackage);
+
 #[skip_serializing_none]
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
@@ -159,8 +301,37 @@ pub struct ComposerPackage {
     pub package: ComposerBasePackage,
 }
 
-#[derive(Clone, Debug, Default, From, Serialize)]
-pub struct ComposerRepositories(Vec<ComposerRepository>);
+/// The repositories of a package.
+///
+/// The notation (a list, or an object with names as keys) is retained when serialized, unless the
+/// repositories were changed, in which case they are serialized as a list.
+#[derive(Clone, Debug, Default)]
+pub struct ComposerRepositories {
+    repositories: Vec<ComposerRepository>,
+    /// The definition as it was deserialized
+    original: Option<Value>,
+}
+
+impl Serialize for ComposerRepositories {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match &self.original {
+            Some(original) => original.serialize(serializer),
+            None => self.repositories.serialize(serializer),
+        }
+    }
+}
+
+impl From<Vec<ComposerRepository>> for ComposerRepositories {
+    fn from(repositories: Vec<ComposerRepository>) -> Self {
+        Self {
+            repositories,
+            original: None,
+        }
+    }
+}
 
 impl<'de> Deserialize<'de> for ComposerRepositories {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -182,7 +353,9 @@ impl<'de> Deserialize<'de> for ComposerRepositories {
             {
                 let mut values: Vec<ComposerRepository> =
                     Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                let mut original = Vec::with_capacity(values.capacity());
                 while let Some(value) = seq.next_element::<Value>()? {
+                    original.push(value.clone());
                     let repo = serde_json::from_value::<ComposerRepository>(value.clone())
                         .or_else(|_| {
                             serde_json::from_value::<ComposerRepositoryDisablement>(value)
@@ -193,7 +366,10 @@ impl<'de> Deserialize<'de> for ComposerRepositories {
                         })?;
                     values.push(repo);
                 }
-                Ok(ComposerRepositories(values))
+                Ok(ComposerRepositories {
+                    repositories: values,
+                    original: Some(Value::Array(original)),
+                })
             }
 
             fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
@@ -201,8 +377,10 @@ impl<'de> Deserialize<'de> for ComposerRepositories {
                 A: MapAccess<'de>,
             {
                 let mut values = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                let mut original = serde_json::Map::new();
                 // we fetch entries as serde_json::Value objects so that we can handle the disabled repo (key: $name, value: false) case
                 while let Some((key, value)) = map.next_entry::<String, Value>()? {
+                    original.insert(key.clone(), value.clone());
                     let repo = serde_json::from_value::<ComposerRepository>(value.clone())
                         .or_else(|_| {
                             // try de-serializing as a boolean false
@@ -233,7 +411,10 @@ impl<'de> Deserialize<'de> for ComposerRepositories {
                         })?;
                     values.push(repo);
                 }
-                Ok(ComposerRepositories(values))
+                Ok(ComposerRepositories {
+                    repositories: values,
+                    original: Some(Value::Object(original)),
+                })
             }
         }
 
@@ -246,15 +427,49 @@ impl<'de> Deserialize<'de> for ComposerRepositories {
 #[allow(clippy::iter_without_into_iter)]
 impl ComposerRepositories {
     pub fn iter(&self) -> std::slice::Iter<'_, ComposerRepository> {
-        self.0.iter()
+        self.repositories.iter()
     }
 
     pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, ComposerRepository> {
-        self.0.iter_mut()
+        // the repositories may be changed, so the original definition no longer applies
+        self.original = None;
+        self.repositories.iter_mut()
+    }
+}
+
+/// A value that can be a single string or a list of strings, like `license` or `bin`.
+///
+/// Retains the original notation when serialized.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ComposerStringOrList {
+    Single(String),
+    List(Vec<String>),
+}
+
+impl std::ops::Deref for ComposerStringOrList {
+    type Target = [String];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            ComposerStringOrList::Single(value) => std::slice::from_ref(value),
+            ComposerStringOrList::List(values) => values,
+        }
+    }
+}
+
+impl From<String> for ComposerStringOrList {
+    fn from(value: String) -> Self {
+        Self::Single(value)
+    }
+}
+
+impl From<Vec<String>> for ComposerStringOrList {
+    fn from(values: Vec<String>) -> Self {
+        Self::List(values)
     }
 }
 
-#[serde_as]
 #[skip_serializing_none]
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
@@ -268,18 +483,16 @@ pub struct ComposerBasePackage {
     pub authors: Option<Vec<ComposerPackageAuthor>>,
     pub autoload: Option<ComposerPackageAutoload>,
     pub autoload_dev: Option<ComposerPackageAutoload>,
-    #[serde_as(as = "Option<OneOrMany<_, PreferOne>>")]
-    pub bin: Option<Vec<String>>,
+    pub bin: Option<ComposerStringOrList>,
     pub conflict: Option<IndexMap<String, String>>,
     pub description: Option<String>,
     pub dist: Option<ComposerPackageDist>,
     pub extra: Option<Value>,
     pub funding: Option<Vec<ComposerPackageFunding>>,
-    pub homepage: Option<Url>,
+    pub homepage: Option<String>, // not a Url, since that would normalize the value
     pub include_path: Option<Vec<String>>,
     pub keywords: Option<Vec<String>>,
-    #[serde_as(as = "Option<OneOrMany<_, PreferOne>>")]
-    pub license: Option<Vec<String>>,
+    pub license: Option<ComposerStringOrList>,
     pub minimum_stability: Option<ComposerStability>,
     pub non_feature_branches: Option<Vec<String>>,
     pub prefer_stable: Option<bool>,
@@ -289,38 +502,95 @@ pub struct ComposerBasePackage {
     pub repositories: Option<ComposerRepositories>,
     pub require: Option<IndexMap<String, String>>,
     pub require_dev: Option<IndexMap<String, String>>,
+    pub scripts: Option<ComposerScripts>,
     pub scripts_descriptions: Option<IndexMap<String, String>>,
     pub source: Option<ComposerPackageSource>,
     pub support: Option<IndexMap<String, String>>,
     pub suggest: Option<IndexMap<String, String>>,
     pub target_dir: Option<String>,
     pub time: Option<String>, // TODO: "Package release date, in 'YYYY-MM-DD', 'YYYY-MM-DD HH:MM:SS' or 'YYYY-MM-DDTHH:MM:SSZ' format.", but in practice it uses DateTime::__construct(), which can parse a lot of formats
+    /// All other fields, retained as-is so that serialization is lossless
+    #[serde(flatten)]
+    pub extra_fields: IndexMap<String, Value>,
 }
 
+/// The `config` section of a root package.
+///
+/// Commonly used keys are typed; all others are retained as-is in `other`.
 #[skip_serializing_none]
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct ComposerConfig {
-    #[serde(rename = "cache-files-ttl")]
-    pub cache_files_ttl: Option<u32>,
-    #[serde(rename = "discard-changes")]
-    pub discard_changes: Option<bool>,
-    #[serde(rename = "allow-plugins")]
     pub allow_plugins: Option<ComposerConfigAllowPlugins>,
+    pub apcu_autoloader: Option<bool>,
+    pub audit: Option<ComposerConfigAudit>,
+    pub bin_dir: Option<String>,
+    pub cache_files_ttl: Option<u32>,
+    pub classmap_authoritative: Option<bool>,
+    pub discard_changes: Option<ComposerConfigDiscardChanges>,
+    pub github_protocols: Option<Vec<String>>,
+    pub optimize_autoloader: Option<bool>,
+    pub platform: Option<IndexMap<String, ComposerConfigPlatformPackage>>,
+    pub platform_check: Option<ComposerConfigPlatformCheck>,
+    pub process_timeout: Option<u32>,
+    pub secure_http: Option<bool>,
+    pub vendor_dir: Option<String>,
+    #[serde(flatten)]
+    pub other: IndexMap<String, Value>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
 pub enum ComposerConfigAllowPlugins {
     Boolean(bool),
     List(IndexMap<String, bool>),
 }
 
+#[skip_serializing_none]
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ComposerConfigAudit {
+    pub abandoned: Option<ComposerConfigAuditAbandoned>,
+    #[serde(flatten)]
+    pub other: IndexMap<String, Value>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComposerConfigAuditAbandoned {
+    Ignore,
+    Report,
+    Fail,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ComposerConfigDiscardChanges {
+    Boolean(bool),
+    Stash(MustBe!("stash")),
+}
+
+/// A package version to pretend is installed, or `false` to pretend the package is not installed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ComposerConfigPlatformPackage {
+    Version(String),
+    Disabled(MustBe!(false)),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ComposerConfigPlatformCheck {
+    Boolean(bool),
+    PhpOnly(MustBe!("php-only")),
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ComposerStability {
     Dev = 20,
     Alpha = 15,
     Beta = 10,
-    #[serde(alias = "RC")]
+    #[serde(rename = "RC", alias = "rc")]
     Rc = 5,
     #[default]
     Stable = 0,
@@ -357,11 +627,13 @@ impl TryFrom<u8> for ComposerStability {
 }
 impl From<PhpAssocArray<ComposerStability>> for PhpAssocArray<u8> {
     fn from(value: PhpAssocArray<ComposerStability>) -> Self {
-        value
-            .iter()
-            .map(|(k, v)| (k.clone(), v.clone() as u8))
-            .collect::<IndexMap<String, u8>>()
-            .into()
+        PhpAssocArray {
+            entries: value
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone() as u8))
+                .collect::<IndexMap<String, u8>>(),
+            empty_array_notation: value.empty_array_notation,
+        }
     }
 }
 impl TryFrom<PhpAssocArray<u8>> for PhpAssocArray<ComposerStability> {
@@ -376,7 +648,10 @@ impl TryFrom<PhpAssocArray<u8>> for PhpAssocArray<ComposerStability> {
             })
             .collect::<Result<IndexMap<String, ComposerStability>, _>>();
         match ret {
-            Ok(v) => Ok(v.into()),
+            Ok(entries) => Ok(PhpAssocArray {
+                entries,
+                empty_array_notation: value.empty_array_notation,
+            }),
             Err(e) => Err(e),
         }
     }
@@ -400,7 +675,7 @@ impl Default for ComposerPackageAbandoned {
 pub struct ComposerPackageAuthor {
     pub name: String,
     pub email: Option<String>, // TODO: could be EmailAddress, but Composer only warns
-    pub homepage: Option<Url>,
+    pub homepage: Option<String>, // not a Url, since that would normalize the value
     pub role: Option<String>,
 }
 
@@ -409,11 +684,8 @@ pub struct ComposerPackageAuthor {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct ComposerPackageAutoload {
-    // map values for the next two can be string or list of strings
-    #[serde_as(as = "Option<IndexMap<_, OneOrMany<_, PreferOne>>>")]
-    pub psr_0: Option<IndexMap<String, Vec<String>>>,
-    #[serde_as(as = "Option<IndexMap<_, OneOrMany<_, PreferOne>>>")]
-    pub psr_4: Option<IndexMap<String, Vec<String>>>,
+    pub psr_0: Option<IndexMap<String, ComposerStringOrList>>,
+    pub psr_4: Option<IndexMap<String, ComposerStringOrList>>,
     pub classmap: Option<Vec<String>>,
     pub files: Option<Vec<String>>,
     pub exclude_from_classmap: Option<Vec<String>>,
@@ -700,17 +972,94 @@ impl From<String> for ComposerUrlOrScpLikeOrPathUrl {
     }
 }
 
+// PHP arrays encode as JSON lists if their keys are sequential, and as JSON objects otherwise
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PhpArray<T> {
+    List(Vec<T>),
+    Map(IndexMap<String, T>),
+}
+
+impl<T> IntoIterator for PhpArray<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            PhpArray::List(v) => v,
+            PhpArray::Map(m) => m.into_values().collect(),
+        }
+        .into_iter()
+    }
+}
+
+/// The inline package list of a Composer repository's `packages.json`.
+///
+/// Composer accepts the `packages` key in either of these forms:
+/// - an object of package names, each with an object of versions and package definitions as value
+///   (e.g. `{"vendor/foo": {"1.0.0": {…}}}`), which is what Composer and Satis generate
+/// - a list of lists of package definitions (e.g. `[[{…}, {…}]]`), which is what the Heroku
+///   platform repositories use
+///
+/// Both are normalized into a flat list of [`ComposerPackage`s](ComposerPackage), in their original order.
+#[derive(Clone, Debug, Default, From)]
+pub struct ComposerRepositoryPackages(Vec<ComposerPackage>);
+
+impl<'de> Deserialize<'de> for ComposerRepositoryPackages {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        PhpArray::<PhpArray<ComposerPackage>>::deserialize(deserializer)
+            .map(|outer| outer.into_iter().flatten().collect::<Vec<_>>())
+            .map(ComposerRepositoryPackages)
+            .map_err(|_| {
+                Error::custom(
+                    "expected an object of package names and versions, or a list of package lists",
+                )
+            })
+    }
+}
+
+impl Serialize for ComposerRepositoryPackages {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // the list of lists notation needs no bookkeeping of names and versions
+        [&self.0].serialize(serializer)
+    }
+}
+
+#[allow(clippy::iter_without_into_iter)]
+impl ComposerRepositoryPackages {
+    pub fn iter(&self) -> std::slice::Iter<'_, ComposerPackage> {
+        self.0.iter()
+    }
+}
+
+/// A Composer repository's `packages.json`, with the packages inlined in the `packages` key.
+///
+/// Other (lazy loading) mechanisms such as `metadata-url`, `providers-url` or `includes` are not supported.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ComposerRepositoryMetadata {
+    #[serde(default)]
+    pub packages: ComposerRepositoryPackages,
+}
+
 #[serde_as]
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
-#[serde(rename_all = "kebab-case")]
+#[serde(remote = "Self", rename_all = "kebab-case")]
 pub struct ComposerLock {
     pub content_hash: String, // since 1.0: https://github.com/composer/composer/pull/4140
     pub packages: Vec<ComposerPackage>,
     pub packages_dev: Vec<ComposerPackage>, // could be null before 1.1.0: https://github.com/composer/composer/pull/5224
     pub platform: PhpAssocArray<String>,
     pub platform_dev: PhpAssocArray<String>,
-    pub platform_overrides: Option<IndexMap<String, String>>, // since 1.0: https://github.com/composer/composer/commit/a57c51e8d78156612e49dec1c54d3184f260f144
-    // pub aliases: IndexMap<String, ComposerPackage>, // since 1.0: https://github.com/composer/composer/pull/350 - TODO: do we need to handle these?
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform_overrides: Option<IndexMap<String, ComposerConfigPlatformPackage>>, // since 1.0: https://github.com/composer/composer/commit/a57c51e8d78156612e49dec1c54d3184f260f144
+    #[serde(default)]
+    pub aliases: Vec<ComposerLockAlias>, // since 1.0: https://github.com/composer/composer/pull/350
     pub minimum_stability: ComposerStability, // since 1.0: https://github.com/composer/composer/pull/592
     #[serde_as(as = "TryFromInto<PhpAssocArray<u8>>")]
     pub stability_flags: PhpAssocArray<ComposerStability>, // since 1.0: https://github.com/composer/composer/pull/592
@@ -719,6 +1068,24 @@ pub struct ComposerLock {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub plugin_api_version: Option<String>, // since 1.10.0: https://github.com/composer/composer/commit/0b9c658bef426a56dc3971e614028ff5078bcd95
+    /// All other fields (like `_readme`), retained as-is so that serialization is lossless
+    #[serde(flatten)]
+    pub extra_fields: IndexMap<String, Value>,
+    /// The key order of the deserialized document, which serialization retains
+    #[serde(skip)]
+    pub key_order: ComposerKeyOrder,
+}
+
+impl_serde_with_key_order!(ComposerLock);
+
+/// An inline alias from the root package's requirements, e.g. `dev-main as 2.0.x-dev`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ComposerLockAlias {
+    pub package: String,
+    /// The normalized version of the aliased package
+    pub version: String,
+    pub alias: String,
+    pub alias_normalized: String,
 }
 
 #[cfg(test)]
@@ -729,6 +1096,20 @@ mod tests {
 
     use serde_test::{Token, assert_de_tokens, assert_de_tokens_error, assert_tokens};
 
+    /// An empty lock file fixture, for tests that only need a few fields set.
+    pub(crate) const EMPTY_LOCK_FIXTURE: &str = "tests/fixtures/composer-emptyhashesasarrays.lock";
+
+    /// A [`ComposerLock`] with the given fields, and all others from [`EMPTY_LOCK_FIXTURE`].
+    pub(crate) fn lock_with(fields: Value) -> ComposerLock {
+        let mut lock =
+            serde_json::from_slice::<Value>(&fs::read(EMPTY_LOCK_FIXTURE).unwrap()).unwrap();
+        let Value::Object(fields) = fields else {
+            panic!("fields must be an object");
+        };
+        lock.as_object_mut().unwrap().extend(fields);
+        serde_json::from_value(lock).unwrap()
+    }
+
     #[derive(Debug, Deref, Deserialize, PartialEq, Serialize)]
     #[serde(transparent)]
     struct ArrayIfEmpty(PhpAssocArray<String>);
@@ -736,7 +1117,7 @@ mod tests {
     #[test]
     fn test_php_assoc_array_populated() {
         assert_de_tokens(
-            &ArrayIfEmpty(PhpAssocArray(IndexMap::from([(
+            &ArrayIfEmpty(PhpAssocArray::from(IndexMap::from([(
                 "foo".to_string(),
                 "bar".to_string(),
             )]))),
@@ -873,14 +1254,107 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_repository_metadata() {
+        let package = serde_json::json!({"name": "heroku-sys/php", "version": "8.4.21", "type": "heroku-sys-php"});
+        for case in [
+            serde_json::json!({"packages": [[package]]}),
+            serde_json::json!({"packages": {"heroku-sys/php": {"8.4.21": package}}}),
+            serde_json::json!({"packages": {"heroku-sys/php": [package]}}),
+        ] {
+            let metadata: ComposerRepositoryMetadata = serde_json::from_value(case).unwrap();
+            let packages = metadata.packages.iter().collect::<Vec<_>>();
+            assert_eq!(packages.len(), 1);
+            assert_eq!(packages[0].name, "heroku-sys/php");
+            assert_eq!(packages[0].version, "8.4.21");
+            assert_eq!(packages[0].package.kind.as_deref(), Some("heroku-sys-php"));
+        }
+
+        let metadata: ComposerRepositoryMetadata =
+            serde_json::from_value(serde_json::json!({"packages": [[]]})).unwrap();
+        assert_eq!(metadata.packages.iter().count(), 0);
+        assert!(
+            serde_json::from_value::<ComposerRepositoryMetadata>(
+                serde_json::json!({"packages": "nope"})
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_composer_config() {
+        let input = serde_json::json!({
+            "allow-plugins": {"my-organization/*": true},
+            "apcu-autoloader": true,
+            "audit": {"abandoned": "fail", "ignore": ["CVE-1234"]},
+            "bin-dir": "bin",
+            "classmap-authoritative": false,
+            "discard-changes": "stash",
+            "github-protocols": ["https"],
+            "optimize-autoloader": true,
+            "platform": {"php": "8.3.0", "ext-mongo": false},
+            "platform-check": "php-only",
+            "process-timeout": 600,
+            "secure-http": true,
+            "vendor-dir": "lib/vendor",
+            "use-github-api": false,
+        });
+        let config: ComposerConfig = serde_json::from_value(input.clone()).unwrap();
+        assert!(matches!(
+            config.allow_plugins,
+            Some(ComposerConfigAllowPlugins::List(_))
+        ));
+        assert_eq!(
+            config
+                .audit
+                .as_ref()
+                .and_then(|audit| audit.abandoned.clone()),
+            Some(ComposerConfigAuditAbandoned::Fail)
+        );
+        assert!(matches!(
+            config.discard_changes,
+            Some(ComposerConfigDiscardChanges::Stash(_))
+        ));
+        assert!(matches!(
+            config
+                .platform
+                .as_ref()
+                .and_then(|platform| platform.get("ext-mongo")),
+            Some(ComposerConfigPlatformPackage::Disabled(_))
+        ));
+        assert!(matches!(
+            config.platform_check,
+            Some(ComposerConfigPlatformCheck::PhpOnly(_))
+        ));
+        assert_eq!(config.vendor_dir.as_deref(), Some("lib/vendor"));
+        assert_eq!(
+            config.other.get("use-github-api"),
+            Some(&Value::Bool(false))
+        );
+        assert_json_diff::assert_json_eq!(config, input);
+
+        let config: ComposerConfig =
+            serde_json::from_value(serde_json::json!({"allow-plugins": false})).unwrap();
+        assert!(matches!(
+            config.allow_plugins,
+            Some(ComposerConfigAllowPlugins::Boolean(false))
+        ));
+        assert!(
+            serde_json::from_value::<ComposerConfig>(
+                serde_json::json!({"platform-check": "sometimes"})
+            )
+            .is_err()
+        );
+    }
+
     #[rstest]
     fn test_composer_json(
         #[files("tests/fixtures/*.json")]
         #[exclude(r"\.xfail\.json$")]
         path: PathBuf,
     ) {
-        let composer_json = std::io::BufReader::new(fs::File::open(&path).unwrap());
-        let package: ComposerRootPackage = serde_json::from_reader(composer_json).unwrap();
+        let composer_json = fs::read(&path).unwrap();
+        let package: ComposerRootPackage = serde_json::from_slice(&composer_json).unwrap();
         // The test cases may have special testing related instructions in the _comment field
         if let Some(Value::Array(ref comments)) = package.package.comment {
             comments
@@ -904,6 +1378,14 @@ mod tests {
                         // The _comment fields obviously differ, so we discard them before comparing
                         ours.package.comment = None;
                         theirs.package.comment = None;
+                        // Repositories retain their notation, so we compare them as lists
+                        let [our_repositories, their_repositories] =
+                            [&mut ours, &mut theirs].map(|package| {
+                                package.package.repositories.take().map(|repositories| {
+                                    repositories.iter().cloned().collect::<Vec<_>>()
+                                })
+                            });
+                        assert_json_diff::assert_json_eq!(our_repositories, their_repositories);
                         assert_json_diff::assert_json_eq!(ours, theirs);
                     }
                     _ => panic!("unexpected magic testing comment instruction"),
@@ -919,6 +1401,110 @@ mod tests {
         serde_json::from_slice::<ComposerRootPackage>(&composer_json).unwrap();
     }
 
+    #[test]
+    fn test_extra_fields() {
+        let input = serde_json::json!({
+            "name": "acme/app",
+            "require": {"php": "^8.3"},
+            "scripts-aliases": {"test": ["t"]},
+            "x-custom": {"nested": [1, 2, 3]},
+        });
+        let package = serde_json::from_value::<ComposerRootPackage>(input.clone()).unwrap();
+        assert_eq!(
+            package.package.extra_fields.keys().collect::<Vec<_>>(),
+            vec!["scripts-aliases", "x-custom"]
+        );
+        assert_eq!(serde_json::to_value(&package).unwrap(), input);
+
+        let mut lock = lock_with(serde_json::json!({
+            "packages": [{"name": "acme/lib", "version": "1.0.0", "notification-url": "https://packagist.org/downloads/"}],
+            "platform-dev": {},
+        }));
+        assert!(lock.extra_fields.contains_key("_readme"));
+        assert!(
+            lock.packages[0]
+                .package
+                .extra_fields
+                .contains_key("notification-url")
+        );
+
+        lock.extra_fields.clear();
+        let lock = serde_json::to_value(&lock).unwrap();
+        assert_eq!(lock.get("_readme"), None);
+        // the notation of empty objects is retained
+        assert_eq!(lock["platform"], serde_json::json!([]));
+        assert_eq!(lock["platform-dev"], serde_json::json!({}));
+    }
+
+    /// Deserializing and serializing again must not lose or change any information, including the
+    /// order of keys and the notation of values; only whitespace and escaping may differ.
+    #[rstest]
+    fn test_round_trip(
+        #[files("tests/fixtures/*.json")]
+        #[files("tests/fixtures/*.lock")]
+        #[exclude(r"\.xfail\.json$")]
+        #[files("../buildpacks/php/tests/fixtures/platform/generator/*/composer.json")]
+        #[files("../buildpacks/php/tests/fixtures/platform/generator/*/composer.lock")]
+        path: PathBuf,
+    ) {
+        let original = fs::read(&path).unwrap();
+        let reserialized = if path.extension().is_some_and(|ext| ext == "lock") {
+            serde_json::to_string(&serde_json::from_slice::<ComposerLock>(&original).unwrap())
+        } else {
+            serde_json::to_string(
+                &serde_json::from_slice::<ComposerRootPackage>(&original).unwrap(),
+            )
+        }
+        .unwrap();
+        // re-encoding the document as-is normalizes whitespace and escaping only
+        assert_eq!(
+            reserialized,
+            serde_json::to_string(&serde_json::from_slice::<Value>(&original).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_key_order() {
+        let package = serde_json::from_str::<ComposerRootPackage>(
+            r#"{"require": {"php": "*"}, "name": "acme/app", "x-custom": true, "config": {"vendor-dir": "lib", "bin-dir": "bin"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_string(&package).unwrap(),
+            r#"{"require":{"php":"*"},"name":"acme/app","x-custom":true,"config":{"vendor-dir":"lib","bin-dir":"bin"}}"#
+        );
+
+        // fields that were not in the original document come last
+        let mut package = package;
+        package.version = Some("1.0.0".to_string());
+        assert_eq!(
+            serde_json::to_string(&package).unwrap(),
+            r#"{"require":{"php":"*"},"name":"acme/app","x-custom":true,"config":{"vendor-dir":"lib","bin-dir":"bin"},"version":"1.0.0"}"#
+        );
+
+        // repositories keep their notation, unless they are changed
+        let mut package = serde_json::from_str::<ComposerRootPackage>(
+            r#"{"repositories": {"acme": {"url": "https://packages.acme.com", "type": "composer"}, "packagist.org": false}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_string(&package).unwrap(),
+            r#"{"repositories":{"acme":{"url":"https://packages.acme.com","type":"composer"},"packagist.org":false}}"#
+        );
+        for repository in package.package.repositories.as_mut().unwrap().iter_mut() {
+            if let ComposerRepository::Composer { canonical, .. } = repository {
+                canonical.replace(false);
+            }
+        }
+        assert_eq!(
+            serde_json::to_value(&package).unwrap()["repositories"],
+            serde_json::json!([
+                {"name": "acme", "type": "composer", "url": "https://packages.acme.com/", "canonical": false},
+                {"packagist.org": false},
+            ])
+        );
+    }
+
     #[rstest]
     fn test_composer_lock(#[files("tests/fixtures/*.lock")] path: PathBuf) {
         let composer_lock = fs::read(&path).unwrap();
@@ -0,0 +1,279 @@
+//! Composer scripts, as defined in the `scripts` section of `composer.json`.
+//!
+//! Parsing of individual commands mirrors `Composer\EventDispatcher\EventDispatcher`.
+
+use derive_more::Deref;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The scripts of a package, by name (an event name like `post-install-cmd`, or a custom name).
+#[derive(Clone, Debug, Default, Deref, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ComposerScripts(IndexMap<String, ComposerScript>);
+
+impl ComposerScripts {
+    /// Expands the script with the given name into the list of commands that would run for it.
+    ///
+    /// References to other scripts (`@other-script`) are replaced, recursively, with their commands.
+    /// Any arguments given in a reference are not applied to the commands it expands into.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the script, or a script it references, is not defined, or if scripts
+    /// reference each other in a loop.
+    pub fn expand(
+        &self,
+        name: &str,
+    ) -> Result<Vec<ComposerScriptCommand>, ComposerScriptExpansionError> {
+        let mut commands = Vec::new();
+        self.expand_into(name, &mut vec![], &mut commands)?;
+        Ok(commands)
+    }
+
+    fn expand_into<'a>(
+        &'a self,
+        name: &'a str,
+        stack: &mut Vec<&'a str>,
+        commands: &mut Vec<ComposerScriptCommand>,
+    ) -> Result<(), ComposerScriptExpansionError> {
+        if stack.contains(&name) {
+            return Err(ComposerScriptExpansionError::Recursion(name.to_string()));
+        }
+        let script = self
+            .0
+            .get(name)
+            .ok_or_else(|| ComposerScriptExpansionError::UndefinedScript(name.to_string()))?;
+
+        stack.push(name);
+        for command in script.commands() {
+            match command {
+                ComposerScriptCommand::Reference { name, .. } => {
+                    self.expand_into(name, stack, commands)?;
+                }
+                command => commands.push(command.clone()),
+            }
+        }
+        stack.pop();
+
+        Ok(())
+    }
+}
+
+impl From<IndexMap<String, ComposerScript>> for ComposerScripts {
+    fn from(value: IndexMap<String, ComposerScript>) -> Self {
+        Self(value)
+    }
+}
+
+/// A script, which is a single command or a list of commands.
+///
+/// Retains the original notation when serialized.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ComposerScript {
+    Single(ComposerScriptCommand),
+    Multiple(Vec<ComposerScriptCommand>),
+    /// An object of commands and their types (like `symfony-cmd`), as used by Symfony Flex for
+    /// `auto-scripts`; these are run by Flex itself, not by Composer
+    Map(IndexMap<String, String>),
+}
+
+impl ComposerScript {
+    /// The commands of this script, in order.
+    ///
+    /// This is empty for a [`ComposerScript::Map`], since Composer does not run those commands.
+    #[must_use]
+    pub fn commands(&self) -> &[ComposerScriptCommand] {
+        match self {
+            ComposerScript::Single(command) => std::slice::from_ref(command),
+            ComposerScript::Multiple(commands) => commands,
+            ComposerScript::Map(_) => &[],
+        }
+    }
+}
+
+/// A command in a script.
+///
+/// Serializes and deserializes as the original string, e.g. `@php bin/console cache:clear`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum ComposerScriptCommand {
+    /// `@php …`: runs the given arguments with the PHP binary Composer is running on
+    Php(String),
+    /// `@composer …`: runs the given arguments with the Composer binary
+    Composer(String),
+    /// `@putenv …`: sets (`FOO=bar`) or unsets (`FOO`) an environment variable for following commands
+    Putenv(String),
+    /// `@name …`: runs another script, optionally with the given arguments
+    Reference { name: String, args: Option<String> },
+    /// `Class::method`: calls a static PHP method
+    Callback(String),
+    /// Any other command, which is run by the shell
+    Shell(String),
+}
+
+impl From<String> for ComposerScriptCommand {
+    fn from(value: String) -> Self {
+        if let Some(rest) = value.strip_prefix("@php ") {
+            Self::Php(rest.to_string())
+        } else if let Some(rest) = value.strip_prefix("@composer ") {
+            Self::Composer(rest.to_string())
+        } else if let Some(rest) = value.strip_prefix("@putenv ") {
+            Self::Putenv(rest.to_string())
+        } else if let Some(rest) = value.strip_prefix('@') {
+            let (name, args) = rest
+                .split_once(' ')
+                .map_or((rest, None), |(name, args)| (name, Some(args.to_string())));
+            Self::Reference {
+                name: name.to_string(),
+                args,
+            }
+        } else if value.contains("::") && !value.contains(' ') {
+            Self::Callback(value)
+        } else {
+            Self::Shell(value)
+        }
+    }
+}
+
+impl From<ComposerScriptCommand> for String {
+    fn from(value: ComposerScriptCommand) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for ComposerScriptCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComposerScriptCommand::Php(args) => write!(f, "@php {args}"),
+            ComposerScriptCommand::Composer(args) => write!(f, "@composer {args}"),
+            ComposerScriptCommand::Putenv(args) => write!(f, "@putenv {args}"),
+            ComposerScriptCommand::Reference { name, args: None } => write!(f, "@{name}"),
+            ComposerScriptCommand::Reference {
+                name,
+                args: Some(args),
+            } => write!(f, "@{name} {args}"),
+            ComposerScriptCommand::Callback(command) | ComposerScriptCommand::Shell(command) => {
+                write!(f, "{command}")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ComposerScriptExpansionError {
+    /// The script with the given name is not defined
+    UndefinedScript(String),
+    /// The script with the given name (indirectly) references itself
+    Recursion(String),
+}
+
+impl fmt::Display for ComposerScriptExpansionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComposerScriptExpansionError::UndefinedScript(name) => {
+                write!(f, "Reference to undefined script {name}")
+            }
+            ComposerScriptExpansionError::Recursion(name) => {
+                write!(f, "Script {name} references itself")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ComposerScriptExpansionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case("@php bin/console cache:clear", ComposerScriptCommand::Php("bin/console cache:clear".to_string()))]
+    #[case("@composer dump-autoload -o", ComposerScriptCommand::Composer("dump-autoload -o".to_string()))]
+    #[case("@putenv COMPOSER=foo.json", ComposerScriptCommand::Putenv("COMPOSER=foo.json".to_string()))]
+    #[case("@auto-scripts", ComposerScriptCommand::Reference { name: "auto-scripts".to_string(), args: None })]
+    #[case("@test --filter Foo", ComposerScriptCommand::Reference { name: "test".to_string(), args: Some("--filter Foo".to_string()) })]
+    #[case("@php", ComposerScriptCommand::Reference { name: "php".to_string(), args: None })]
+    #[case("Acme\\Installer::postInstall", ComposerScriptCommand::Callback("Acme\\Installer::postInstall".to_string()))]
+    #[case("echo foo::bar", ComposerScriptCommand::Shell("echo foo::bar".to_string()))]
+    #[case("npm run build", ComposerScriptCommand::Shell("npm run build".to_string()))]
+    fn test_command(#[case] input: &str, #[case] expected: ComposerScriptCommand) {
+        let command = ComposerScriptCommand::from(input.to_string());
+        assert_eq!(command, expected);
+        assert_eq!(command.to_string(), input);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let input = json!({
+            "compile": "@php artisan optimize",
+            "post-install-cmd": ["@auto-scripts", "Acme\\Installer::postInstall"],
+            "auto-scripts": ["@php bin/console cache:clear"],
+            "flex-scripts": {"cache:clear": "symfony-cmd"},
+        });
+        let scripts = serde_json::from_value::<ComposerScripts>(input.clone()).unwrap();
+        assert!(matches!(
+            scripts.get("compile"),
+            Some(ComposerScript::Single(ComposerScriptCommand::Php(_)))
+        ));
+        assert!(matches!(
+            scripts.get("auto-scripts"),
+            Some(ComposerScript::Multiple(commands)) if commands.len() == 1
+        ));
+        assert!(
+            scripts
+                .get("flex-scripts")
+                .is_some_and(|script| script.commands().is_empty())
+        );
+        assert_eq!(serde_json::to_value(&scripts).unwrap(), input);
+
+        assert!(serde_json::from_value::<ComposerScripts>(json!({"compile": 1})).is_err());
+    }
+
+    #[test]
+    fn test_expand() {
+        let scripts = serde_json::from_value::<ComposerScripts>(json!({
+            "post-install-cmd": ["@auto-scripts", "@build --prod", "echo done"],
+            "auto-scripts": "@php bin/console cache:clear",
+            "build": ["@putenv NODE_ENV=production", "npm run build"],
+            "broken": "@missing",
+            "loop": "@loop-again",
+            "loop-again": ["echo", "@loop"],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            scripts
+                .expand("post-install-cmd")
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "@php bin/console cache:clear",
+                "@putenv NODE_ENV=production",
+                "npm run build",
+                "echo done",
+            ]
+        );
+        assert_eq!(
+            scripts.expand("compile"),
+            Err(ComposerScriptExpansionError::UndefinedScript(
+                "compile".to_string()
+            ))
+        );
+        assert_eq!(
+            scripts.expand("broken"),
+            Err(ComposerScriptExpansionError::UndefinedScript(
+                "missing".to_string()
+            ))
+        );
+        assert_eq!(
+            scripts.expand("loop"),
+            Err(ComposerScriptExpansionError::Recursion("loop".to_string()))
+        );
+    }
+}
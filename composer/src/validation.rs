@@ -0,0 +1,528 @@
+//! Validation of `composer.json` documents.
+//!
+//! The checks mirror those of `Composer\Package\Loader\ValidatingArrayLoader` for package names,
+//! package links, stabilities, repositories and autoload configuration. Problems are located using
+//! JSON pointers (RFC 6901), so they can be reported precisely.
+//!
+//! Invalid package names are reported separately from structural problems by
+//! [`check_package_names`], as a document with such names can still be used.
+
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// A problem with a value in a `composer.json` document.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComposerValidationError {
+    /// The JSON pointer to the offending value, e.g. `/require/foo bar`
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for ComposerValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() {
+            "/"
+        } else {
+            &self.pointer
+        };
+        write!(f, "{pointer}: {}", self.message)
+    }
+}
+
+impl std::error::Error for ComposerValidationError {}
+
+/// The sections of a package that contain links to other packages.
+const LINK_SECTIONS: [&str; 6] = [
+    "require",
+    "require-dev",
+    "conflict",
+    "provide",
+    "replace",
+    "suggest",
+];
+
+/// Repository types that are located using a `url`.
+const URL_REPOSITORY_TYPES: [&str; 13] = [
+    "composer",
+    "vcs",
+    "git",
+    "github",
+    "gitlab",
+    "bitbucket",
+    "git-bitbucket",
+    "hg",
+    "fossil",
+    "perforce",
+    "svn",
+    "artifact",
+    "path",
+];
+
+const AUTOLOAD_TYPES: [&str; 5] = [
+    "psr-0",
+    "psr-4",
+    "classmap",
+    "files",
+    "exclude-from-classmap",
+];
+
+const STABILITIES: [&str; 5] = ["dev", "alpha", "beta", "rc", "stable"];
+
+/// Validates the structure of the given `composer.json` document.
+///
+/// Package names are only checked for their type; use [`check_package_names`] for their format.
+///
+/// # Errors
+///
+/// Returns all [`ComposerValidationError`s](ComposerValidationError) found in the document.
+pub fn validate_composer_json(document: &Value) -> Result<(), Vec<ComposerValidationError>> {
+    let mut errors = Vec::new();
+
+    let Some(root) = document.as_object() else {
+        return Err(vec![error("", "must be an object")]);
+    };
+
+    if let Some(name) = root.get("name")
+        && !name.is_string()
+    {
+        errors.push(error("/name", "must be a string"));
+    }
+
+    for section in LINK_SECTIONS {
+        if let Some(links) = root.get(section) {
+            validate_links(
+                &pointer("", section),
+                links,
+                section != "suggest",
+                &mut errors,
+            );
+        }
+    }
+
+    if let Some(stability) = root.get("minimum-stability") {
+        validate_stability("/minimum-stability", stability, &mut errors);
+    }
+
+    if let Some(repositories) = root.get("repositories") {
+        validate_repositories("/repositories", repositories, &mut errors);
+    }
+
+    for section in ["autoload", "autoload-dev"] {
+        if let Some(autoload) = root.get(section) {
+            validate_autoload(&pointer("", section), autoload, &mut errors);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Builds the JSON pointer for the given key or index in the value at the given parent pointer.
+fn pointer(parent: &str, token: &str) -> String {
+    format!("{parent}/{}", token.replace('~', "~0").replace('/', "~1"))
+}
+
+fn error(pointer: &str, message: impl Into<String>) -> ComposerValidationError {
+    ComposerValidationError {
+        pointer: pointer.to_string(),
+        message: message.into(),
+    }
+}
+
+/// Returns the entries of a JSON object; PHP encodes empty associative arrays as `[]`, so empty
+/// arrays are treated as empty objects, like Composer does.
+fn php_object(value: &Value) -> Option<Vec<(&String, &Value)>> {
+    match value {
+        Value::Object(object) => Some(object.iter().collect()),
+        Value::Array(array) if array.is_empty() => Some(vec![]),
+        _ => None,
+    }
+}
+
+fn is_platform_package_name(name: &str) -> bool {
+    regex!(r"^(?i)(?:php(?:-64bit|-ipv6|-zts|-debug)?|hhvm|(?:ext|lib)-[a-z0-9](?:[_.-]?[a-z0-9]+)*|composer(?:-(?:plugin|runtime)-api)?)$")
+        .is_match(name)
+}
+
+fn is_valid_package_name(name: &str) -> bool {
+    regex!(r"^[a-z0-9](?:[_.-]?[a-z0-9]+)*/[a-z0-9](?:(?:[_.]|-{1,2})?[a-z0-9]+)*$").is_match(name)
+}
+
+/// Checks the format of the package name, and of the names of all linked packages, in the given
+/// `composer.json` document.
+///
+/// Values of the wrong type are skipped, as [`validate_composer_json`] reports those.
+pub fn check_package_names(document: &Value) -> Vec<ComposerValidationError> {
+    let mut problems = Vec::new();
+
+    let Some(root) = document.as_object() else {
+        return problems;
+    };
+
+    if let Some(name) = root.get("name").and_then(Value::as_str)
+        && !is_valid_package_name(name)
+    {
+        problems.push(error(
+            "/name",
+            format!(
+                "'{name}' is not a valid package name; it must be a lowercase vendor name and package name separated by a forward slash, e.g. 'acme/my-package'"
+            ),
+        ));
+    }
+
+    for section in LINK_SECTIONS {
+        let Some(links) = root.get(section).and_then(php_object) else {
+            continue;
+        };
+        for (name, _) in links {
+            // Composer matches case-insensitively here, since names of links are normalized later
+            if !is_platform_package_name(name) && !is_valid_package_name(&name.to_lowercase()) {
+                problems.push(error(
+                    &pointer(&pointer("", section), name),
+                    format!(
+                        "'{name}' is not a valid package name; it must be a vendor name and package name separated by a forward slash, e.g. 'acme/my-package', or a platform package name, e.g. 'php' or 'ext-intl'"
+                    ),
+                ));
+            }
+        }
+    }
+
+    problems
+}
+
+fn validate_links(
+    pointer_to_links: &str,
+    links: &Value,
+    values_are_constraints: bool,
+    errors: &mut Vec<ComposerValidationError>,
+) {
+    let Some(links) = php_object(links) else {
+        errors.push(error(
+            pointer_to_links,
+            "must be an object of package names and version constraints",
+        ));
+        return;
+    };
+
+    for (name, value) in links {
+        if !value.is_string() {
+            errors.push(error(
+                &pointer(pointer_to_links, name),
+                if values_are_constraints {
+                    "must be a version constraint string"
+                } else {
+                    "must be a string"
+                },
+            ));
+        }
+    }
+}
+
+fn validate_stability(pointer: &str, stability: &Value, errors: &mut Vec<ComposerValidationError>) {
+    if !stability
+        .as_str()
+        .is_some_and(|stability| STABILITIES.contains(&stability.to_lowercase().as_str()))
+    {
+        errors.push(error(
+            pointer,
+            "must be one of 'dev', 'alpha', 'beta', 'RC' or 'stable'",
+        ));
+    }
+}
+
+fn validate_repositories(
+    pointer_to_repositories: &str,
+    repositories: &Value,
+    errors: &mut Vec<ComposerValidationError>,
+) {
+    let is_disablement = |repository: &Map<String, Value>| {
+        repository.len() == 1 && repository.values().all(|v| *v == Value::Bool(false))
+    };
+
+    match repositories {
+        Value::Array(repositories) => {
+            for (index, repository) in repositories.iter().enumerate() {
+                let pointer_to_repository = pointer(pointer_to_repositories, &index.to_string());
+                match repository.as_object() {
+                    Some(repository) if is_disablement(repository) => (),
+                    _ => validate_repository(&pointer_to_repository, repository, errors),
+                }
+            }
+        }
+        Value::Object(repositories) => {
+            for (name, repository) in repositories {
+                let pointer_to_repository = pointer(pointer_to_repositories, name);
+                if *repository != Value::Bool(false) {
+                    validate_repository(&pointer_to_repository, repository, errors);
+                }
+            }
+        }
+        _ => errors.push(error(
+            pointer_to_repositories,
+            "must be an array or an object of repository definitions",
+        )),
+    }
+}
+
+fn validate_repository(
+    pointer_to_repository: &str,
+    repository: &Value,
+    errors: &mut Vec<ComposerValidationError>,
+) {
+    let Some(repository) = repository.as_object() else {
+        errors.push(error(
+            pointer_to_repository,
+            "must be a repository definition object, or 'false' to disable a repository",
+        ));
+        return;
+    };
+
+    let pointer_to_type = pointer(pointer_to_repository, "type");
+    let kind = match repository.get("type") {
+        Some(Value::String(kind)) if !kind.is_empty() => kind.as_str(),
+        Some(_) => {
+            errors.push(error(&pointer_to_type, "must be a non-empty string"));
+            return;
+        }
+        None => {
+            errors.push(error(pointer_to_repository, "must have a 'type'"));
+            return;
+        }
+    };
+
+    if URL_REPOSITORY_TYPES.contains(&kind) {
+        match repository.get("url") {
+            Some(Value::String(url)) if !url.is_empty() => (),
+            Some(_) => errors.push(error(
+                &pointer(pointer_to_repository, "url"),
+                "must be a non-empty string",
+            )),
+            None => errors.push(error(
+                pointer_to_repository,
+                format!("must have a 'url' for type '{kind}'"),
+            )),
+        }
+    } else if kind == "package" {
+        let pointer_to_package = pointer(pointer_to_repository, "package");
+        match repository.get("package") {
+            Some(Value::Object(_)) => (),
+            Some(Value::Array(packages)) => {
+                for (index, package) in packages.iter().enumerate() {
+                    if !package.is_object() {
+                        errors.push(error(
+                            &pointer(&pointer_to_package, &index.to_string()),
+                            "must be a package definition object",
+                        ));
+                    }
+                }
+            }
+            Some(_) => errors.push(error(
+                &pointer_to_package,
+                "must be a package definition object or an array of them",
+            )),
+            None => errors.push(error(
+                pointer_to_repository,
+                "must have a 'package' for type 'package'",
+            )),
+        }
+    }
+    // other types may be provided by plugins, so we cannot know what they need
+}
+
+fn validate_autoload(
+    pointer_to_autoload: &str,
+    autoload: &Value,
+    errors: &mut Vec<ComposerValidationError>,
+) {
+    let Some(autoload) = php_object(autoload) else {
+        errors.push(error(pointer_to_autoload, "must be an object"));
+        return;
+    };
+
+    let is_path_list = |value: &Value| {
+        value
+            .as_array()
+            .is_some_and(|paths| paths.iter().all(Value::is_string))
+    };
+
+    for (kind, config) in autoload {
+        let pointer_to_config = pointer(pointer_to_autoload, kind);
+        match kind.as_str() {
+            "psr-0" | "psr-4" => {
+                let Some(namespaces) = php_object(config) else {
+                    errors.push(error(
+                        &pointer_to_config,
+                        "must be an object of namespaces and paths",
+                    ));
+                    continue;
+                };
+                for (namespace, paths) in namespaces {
+                    let pointer_to_namespace = pointer(&pointer_to_config, namespace);
+                    if kind == "psr-4" && !namespace.is_empty() && !namespace.ends_with('\\') {
+                        errors.push(error(
+                            &pointer_to_namespace,
+                            format!(
+                                "PSR-4 namespace prefix '{namespace}' must end with a namespace separator, e.g. '{namespace}\\\\'"
+                            ),
+                        ));
+                    }
+                    if !paths.is_string() && !is_path_list(paths) {
+                        errors.push(error(
+                            &pointer_to_namespace,
+                            "must be a path string or an array of path strings",
+                        ));
+                    }
+                }
+            }
+            "classmap" | "files" | "exclude-from-classmap" => {
+                if !is_path_list(config) {
+                    errors.push(error(
+                        &pointer_to_config,
+                        "must be an array of path strings",
+                    ));
+                }
+            }
+            _ => errors.push(error(
+                &pointer_to_config,
+                format!(
+                    "is not a valid autoload type; must be one of '{}'",
+                    AUTOLOAD_TYPES.join("', '")
+                ),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_valid() {
+        let document = json!({
+            "name": "acme/my-app",
+            "require": {"php": "^8.2", "ext-intl": "*", "Monolog/Monolog": "^3.0"},
+            "require-dev": {"phpunit/phpunit": "^11"},
+            "conflict": [],
+            "suggest": {"ext-redis": "For caching"},
+            "minimum-stability": "RC",
+            "repositories": [
+                {"packagist.org": false},
+                {"type": "vcs", "url": "https://github.com/acme/lib"},
+                {"type": "package", "package": [{"name": "acme/thing", "version": "1.0.0"}]},
+                {"type": "some-plugin-type"},
+            ],
+            "autoload": {
+                "psr-4": {"Acme\\": "src/", "": ["lib/", "legacy/"]},
+                "psr-0": {"Acme_": "old/"},
+                "classmap": ["classes/"],
+                "files": ["helpers.php"],
+            },
+        });
+        validate_composer_json(&document).unwrap();
+        assert_eq!(check_package_names(&document), vec![]);
+
+        validate_composer_json(&json!({
+            "repositories": {
+                "packagist.org": false,
+                "acme": {"type": "composer", "url": "https://packages.acme.com"},
+            },
+        }))
+        .unwrap();
+    }
+
+    #[rstest]
+    #[case(json!([]), "/", "must be an object")]
+    #[case(json!({"name": ["acme/app"]}), "/name", "must be a string")]
+    #[case(json!({"require": {"acme/foo": 1}}), "/require/acme~1foo", "must be a version constraint string")]
+    #[case(json!({"require": ["acme/foo"]}), "/require", "must be an object")]
+    #[case(json!({"minimum-stability": "unstable"}), "/minimum-stability", "must be one of")]
+    #[case(json!({"repositories": "nope"}), "/repositories", "must be an array or an object")]
+    #[case(json!({"repositories": [{"url": "https://example.com"}]}), "/repositories/0", "must have a 'type'")]
+    #[case(json!({"repositories": [{"type": "vcs"}]}), "/repositories/0", "must have a 'url' for type 'vcs'")]
+    #[case(json!({"repositories": {"acme": {"type": "composer", "url": 1}}}), "/repositories/acme/url", "must be a non-empty string")]
+    #[case(json!({"repositories": [{"type": "package", "package": ["acme/foo"]}]}), "/repositories/0/package/0", "must be a package definition object")]
+    #[case(json!({"repositories": [true]}), "/repositories/0", "must be a repository definition object")]
+    #[case(json!({"autoload": {"psr-4": {"Acme": "src/"}}}), "/autoload/psr-4/Acme", "must end with a namespace separator")]
+    #[case(json!({"autoload-dev": {"psr-0": {"Acme_": [1]}}}), "/autoload-dev/psr-0/Acme_", "must be a path string")]
+    #[case(json!({"autoload": {"classmap": "src/"}}), "/autoload/classmap", "must be an array of path strings")]
+    #[case(json!({"autoload": {"psr-5": {}}}), "/autoload/psr-5", "is not a valid autoload type")]
+    fn test_invalid(
+        #[case] document: Value,
+        #[case] expected_pointer: &str,
+        #[case] expected_message: &str,
+    ) {
+        let errors = validate_composer_json(&document).unwrap_err();
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert_eq!(
+            errors[0].to_string().split_once(": ").unwrap().0,
+            expected_pointer
+        );
+        assert!(
+            errors[0].message.contains(expected_message),
+            "{}",
+            errors[0].message
+        );
+    }
+
+    #[rstest]
+    #[case(json!({"name": "Acme/App"}), "/name")]
+    #[case(json!({"name": "acme"}), "/name")]
+    #[case(json!({"require": {"foo bar": "*"}}), "/require/foo bar")]
+    #[case(json!({"suggest": {"acme/foo/bar": "For things"}}), "/suggest/acme~1foo~1bar")]
+    fn test_invalid_package_names(#[case] document: Value, #[case] expected_pointer: &str) {
+        // invalid names alone do not make a document invalid
+        validate_composer_json(&document).unwrap();
+
+        let problems = check_package_names(&document);
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert_eq!(problems[0].pointer, expected_pointer);
+        assert!(
+            problems[0].message.contains("is not a valid package name"),
+            "{}",
+            problems[0].message
+        );
+    }
+
+    #[test]
+    fn test_multiple_errors() {
+        let document = json!({
+            "name": "nope",
+            "require": {"foo bar": "*", "acme/baz": false},
+            "minimum-stability": "unstable",
+        });
+        assert_eq!(
+            validate_composer_json(&document)
+                .unwrap_err()
+                .iter()
+                .map(|error| error.pointer.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/require/acme~1baz", "/minimum-stability"]
+        );
+        assert_eq!(
+            check_package_names(&document)
+                .iter()
+                .map(|problem| problem.pointer.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/name", "/require/foo bar"]
+        );
+    }
+
+    #[rstest]
+    fn test_fixtures(
+        #[files("tests/fixtures/*.json")]
+        #[exclude(r"\.xfail\.json$")]
+        path: PathBuf,
+    ) {
+        let document = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        validate_composer_json(&document).unwrap();
+        assert_eq!(check_package_names(&document), vec![]);
+    }
+}
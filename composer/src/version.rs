@@ -0,0 +1,562 @@
+//! Normalization and comparison of Composer versions.
+//!
+//! The behavior mirrors that of `Composer\Semver\VersionParser` and `Composer\Semver\Comparator`,
+//! which in turn rely on PHP's `version_compare()`.
+
+use crate::ComposerStability;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// A package version, like `1.2.3`, `v2.0-RC1`, `2.x-dev` or `dev-main`.
+///
+/// Versions are compared in their normalized form, so `1.2` equals `v1.2.0.0`, and build metadata
+/// such as `+heroku1` is ignored. Serializes and deserializes as the original ("pretty") string.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ComposerVersion {
+    pretty: String,
+    normalized: String,
+}
+
+impl ComposerVersion {
+    /// The normalized form of this version, e.g. `1.2.0.0-RC1` for `v1.2-rc1`.
+    #[must_use]
+    pub fn normalized(&self) -> &str {
+        &self.normalized
+    }
+
+    /// The [`ComposerStability`] of this version.
+    #[must_use]
+    pub fn stability(&self) -> ComposerStability {
+        parse_stability(&self.normalized)
+    }
+
+    /// Whether this version is a named branch like `dev-main`, as opposed to a numeric version.
+    #[must_use]
+    pub fn is_branch(&self) -> bool {
+        self.normalized.starts_with("dev-")
+    }
+
+    /// The major version number, unless this is a named branch.
+    #[must_use]
+    pub fn major(&self) -> Option<u64> {
+        self.numeric_component(0)
+    }
+
+    /// The minor version number, unless this is a named branch or a date based version.
+    #[must_use]
+    pub fn minor(&self) -> Option<u64> {
+        if self.is_date_based() {
+            return None;
+        }
+        self.numeric_component(1)
+    }
+
+    /// Whether this is a date based version like `20100102-203040`, as opposed to a classical one.
+    ///
+    /// Classical versions normalize to four numbers, the first of which has at most five digits.
+    fn is_date_based(&self) -> bool {
+        self.numeric_components().is_some_and(|components| {
+            components.len() != 4 || components.first().is_some_and(|major| major.len() > 5)
+        })
+    }
+
+    fn numeric_components(&self) -> Option<Vec<&str>> {
+        if self.is_branch() {
+            return None;
+        }
+        Some(self.normalized.split('-').next()?.split('.').collect())
+    }
+
+    fn numeric_component(&self, position: usize) -> Option<u64> {
+        self.numeric_components()?.get(position)?.parse().ok()
+    }
+
+    /// The version used for comparisons, in which default branch names are treated as the
+    /// highest possible version, just like Composer does.
+    fn comparable(&self) -> &str {
+        match self.normalized.as_str() {
+            "dev-master" | "dev-default" | "dev-trunk" => "9999999-dev",
+            normalized => normalized,
+        }
+    }
+}
+
+impl fmt::Display for ComposerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pretty)
+    }
+}
+
+impl FromStr for ComposerVersion {
+    type Err = ComposerVersionError;
+
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            pretty: version.to_string(),
+            normalized: normalize(version).map_err(ComposerVersionError)?,
+        })
+    }
+}
+
+impl TryFrom<String> for ComposerVersion {
+    type Error = ComposerVersionError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ComposerVersion> for String {
+    fn from(value: ComposerVersion) -> Self {
+        value.pretty
+    }
+}
+
+impl PartialEq for ComposerVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized == other.normalized
+    }
+}
+
+impl Eq for ComposerVersion {}
+
+impl Hash for ComposerVersion {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized.hash(state);
+    }
+}
+
+impl PartialOrd for ComposerVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ComposerVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        php_version_compare(self.comparable(), other.comparable())
+            // named branches are all equal to version_compare(), but not to us
+            .then_with(|| self.normalized.cmp(&other.normalized))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComposerVersionError(String);
+
+impl fmt::Display for ComposerVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ComposerVersionError {}
+
+/// Normalizes a version string, e.g. `v1.2-RC1` to `1.2.0.0-RC1`, or `2.x-dev` to
+/// `2.9999999.9999999.9999999-dev`.
+pub(crate) fn normalize(version: &str) -> Result<String, String> {
+    let original = version;
+    let mut version = version.trim();
+
+    // strip off aliasing
+    if let Some(captures) = regex!(r"^([^,\s]+) +as +[^,\s]+$").captures(version) {
+        version = captures.get(1).map_or("", |m| m.as_str());
+    }
+    // strip off stability flag
+    if let Some(m) = regex!(r"(?i)@(?:stable|RC|beta|alpha|dev)$").find(version) {
+        version = &version[..m.start()];
+    }
+    // normalize master/trunk/default branches to dev-name, like Composer does for BC
+    if matches!(version, "master" | "trunk" | "default") {
+        return Ok(format!("dev-{version}"));
+    }
+    // if the requirement is branch-like, use the full name
+    if version
+        .get(..4)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("dev-"))
+    {
+        return Ok(format!("dev-{}", &version[4..]));
+    }
+    // strip off build metadata
+    if let Some(captures) = regex!(r"^([^,\s+]+)\+[^\s]+$").captures(version) {
+        version = captures.get(1).map_or("", |m| m.as_str());
+    }
+
+    // classical versioning, or date(time) based versioning
+    let (normalized, captures, index) = if let Some(captures) = regex!(r"(?i)^v?(\d{1,5})(\.\d+)?(\.\d+)?(\.\d+)?[._-]?(?:(stable|beta|b|RC|alpha|a|patch|pl|p)((?:[.-]?\d+)*)?)?([.-]?dev)?$").captures(version) {
+        let normalized = (2..=4).fold(captures[1].to_string(), |normalized, i| {
+            normalized + captures.get(i).map_or(".0", |m| m.as_str())
+        });
+        (normalized, captures, 5)
+    } else if let Some(captures) = regex!(r"(?i)^v?(\d{4}(?:[.:-]?\d{2}){1,6}(?:[.:-]?\d{1,3}){0,2})[._-]?(?:(stable|beta|b|RC|alpha|a|patch|pl|p)((?:[.-]?\d+)*)?)?([.-]?dev)?$").captures(version) {
+        let normalized = regex!(r"\D").replace_all(&captures[1], ".").to_string();
+        (normalized, captures, 2)
+    } else {
+        // dev branches, like `1.x-dev`; names like `foo-dev` need a `dev-` prefix instead
+        return regex!(r"(?i)^(.*?)[.-]?dev$")
+            .captures(version)
+            .map(|captures| normalize_branch(&captures[1]))
+            .filter(|normalized| !normalized.contains("dev-"))
+            .ok_or_else(|| format!("Invalid version string \"{original}\""));
+    };
+
+    let mut normalized = normalized;
+    if let Some(stability) = captures.get(index) {
+        if stability.as_str() == "stable" {
+            return Ok(normalized);
+        }
+        normalized.push('-');
+        normalized.push_str(&expand_stability(stability.as_str()));
+        if let Some(number) = captures.get(index + 1) {
+            normalized.push_str(number.as_str().trim_start_matches(['.', '-']));
+        }
+    }
+    if captures.get(index + 2).is_some() {
+        normalized.push_str("-dev");
+    }
+    Ok(normalized)
+}
+
+/// Normalizes a branch name, e.g. `2.1.x` to `2.1.9999999.9999999-dev`, or `feature` to
+/// `dev-feature`.
+fn normalize_branch(name: &str) -> String {
+    let name = name.trim();
+    match regex!(r"(?i)^v?(\d+)(\.(?:\d+|[x*]))?(\.(?:\d+|[x*]))?(\.(?:\d+|[x*]))?$").captures(name)
+    {
+        Some(captures) => {
+            let version = (1..=4)
+                .map(|i| captures.get(i).map_or(".x", |m| m.as_str()))
+                .collect::<String>();
+            version.replace(['*', 'X', 'x'], "9999999") + "-dev"
+        }
+        None => format!("dev-{name}"),
+    }
+}
+
+fn expand_stability(stability: &str) -> String {
+    match stability.to_lowercase().as_str() {
+        "a" => "alpha".to_string(),
+        "b" => "beta".to_string(),
+        "p" | "pl" => "patch".to_string(),
+        "rc" => "RC".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Returns the [`ComposerStability`] of a (normalized or not) version string.
+pub(crate) fn parse_stability(version: &str) -> ComposerStability {
+    let version = regex!(r"#.+$").replace(version, "");
+    if version.starts_with("dev-") || version.ends_with("-dev") {
+        return ComposerStability::Dev;
+    }
+
+    let version = version.to_lowercase();
+    let captures = regex!(
+        r"[._-]?(?:(stable|beta|b|rc|alpha|a|patch|pl|p)((?:[.-]?\d+)*)?)?([.-]?dev)?(?:\+.*)?$"
+    )
+    .captures(&version);
+    match captures
+        .as_ref()
+        .map(|c| (c.get(1).map(|m| m.as_str()), c.get(3)))
+    {
+        Some((_, Some(_))) => ComposerStability::Dev,
+        Some((Some("beta" | "b"), None)) => ComposerStability::Beta,
+        Some((Some("alpha" | "a"), None)) => ComposerStability::Alpha,
+        Some((Some("rc"), None)) => ComposerStability::Rc,
+        _ => ComposerStability::Stable,
+    }
+}
+
+/// Compares two versions the way PHP's `version_compare()` does.
+pub(crate) fn php_version_compare(a: &str, b: &str) -> Ordering {
+    /// Orders the "special" version forms; numbers are represented by `#`.
+    fn special_form_order(form: &str) -> i8 {
+        [
+            ("dev", 0),
+            ("alpha", 1),
+            ("a", 1),
+            ("beta", 2),
+            ("b", 2),
+            ("RC", 3),
+            ("rc", 3),
+            ("#", 4),
+            ("pl", 5),
+            ("p", 5),
+        ]
+        .into_iter()
+        .find_map(|(name, order)| form.starts_with(name).then_some(order))
+        .unwrap_or(-1)
+    }
+
+    fn starts_with_digit(part: &str) -> bool {
+        part.starts_with(|c: char| c.is_ascii_digit())
+    }
+
+    /// Separates all parts of a version by dots, e.g. `1.0-beta2` to `1.0.beta.2`.
+    fn canonicalize(version: &str) -> String {
+        let mut canonical = String::with_capacity(version.len() * 2);
+        let mut previous = None;
+        for c in version.chars() {
+            let is_digit = c.is_ascii_digit();
+            let is_boundary = previous.is_some_and(|p: char| {
+                p != '.' && !matches!(p, '-' | '_' | '+') && p.is_ascii_digit() != is_digit
+            });
+            if previous.is_some()
+                && (is_boundary || !c.is_ascii_alphanumeric())
+                && !canonical.ends_with('.')
+            {
+                canonical.push('.');
+            }
+            if previous.is_none() || c.is_ascii_alphanumeric() {
+                canonical.push(c);
+            }
+            previous = Some(c);
+        }
+        canonical
+    }
+
+    if a.is_empty() || b.is_empty() {
+        return (!a.is_empty()).cmp(&!b.is_empty());
+    }
+    let canonical_a = canonicalize(a);
+    let canonical_b = canonicalize(b);
+    let parts_a = canonical_a.split('.').collect::<Vec<_>>();
+    let parts_b = canonical_b.split('.').collect::<Vec<_>>();
+
+    for (part_a, part_b) in parts_a.iter().zip(&parts_b) {
+        let ordering = match (starts_with_digit(part_a), starts_with_digit(part_b)) {
+            (true, true) => {
+                let number = |part: &str| part.parse::<u128>().unwrap_or(u128::MAX);
+                number(part_a).cmp(&number(part_b))
+            }
+            (true, false) => special_form_order("#").cmp(&special_form_order(part_b)),
+            (false, true) => special_form_order(part_a).cmp(&special_form_order("#")),
+            (false, false) => special_form_order(part_a).cmp(&special_form_order(part_b)),
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+
+    match parts_a.len().cmp(&parts_b.len()) {
+        Ordering::Greater if starts_with_digit(parts_a[parts_b.len()]) => Ordering::Greater,
+        Ordering::Greater => special_form_order(parts_a[parts_b.len()]).cmp(&4),
+        Ordering::Less if starts_with_digit(parts_b[parts_a.len()]) => Ordering::Less,
+        Ordering::Less => 4.cmp(&special_form_order(parts_b[parts_a.len()])),
+        Ordering::Equal => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1.0.0", "1.0.0.0")]
+    #[case("1.2.3.4", "1.2.3.4")]
+    #[case("1.0.0RC1dev", "1.0.0.0-RC1-dev")]
+    #[case("1.0.0-rC15-dev", "1.0.0.0-RC15-dev")]
+    #[case("1.0.0.RC.15-dev", "1.0.0.0-RC15-dev")]
+    #[case("1.0.0-rc1", "1.0.0.0-RC1")]
+    #[case("1.0.0.pl3-dev", "1.0.0.0-patch3-dev")]
+    #[case("1.0-dev", "1.0.0.0-dev")]
+    #[case("0", "0.0.0.0")]
+    #[case("10.4.13-beta", "10.4.13.0-beta")]
+    #[case("10.4.13beta2", "10.4.13.0-beta2")]
+    #[case("10.4.13beta.2", "10.4.13.0-beta2")]
+    #[case("v1.13.11-beta.0", "1.13.11.0-beta0")]
+    #[case("1.13.11.0-beta0", "1.13.11.0-beta0")]
+    #[case("10.4.13-b", "10.4.13.0-beta")]
+    #[case("10.4.13-b5", "10.4.13.0-beta5")]
+    #[case("v1.0.0", "1.0.0.0")]
+    #[case("2010.01", "2010.01.0.0")]
+    #[case("2010.01.02", "2010.01.02.0")]
+    #[case("2010.1.555", "2010.1.555.0")]
+    #[case("2010.10.200", "2010.10.200.0")]
+    #[case("v20100102", "20100102")]
+    #[case("2010-01-02", "2010.01.02")]
+    #[case("2010-01-02.5", "2010.01.02.5")]
+    #[case("20100102-203040", "20100102.203040")]
+    #[case("20100102203040-10", "20100102203040.10")]
+    #[case("20100102-203040-p1", "20100102.203040-patch1")]
+    #[case("201903.0", "201903.0")]
+    #[case("201903.0-p2", "201903.0-patch2")]
+    #[case("dev-master", "dev-master")]
+    #[case("master", "dev-master")]
+    #[case("dev-trunk", "dev-trunk")]
+    #[case("1.x-dev", "1.9999999.9999999.9999999-dev")]
+    #[case("dev-feature-foo", "dev-feature-foo")]
+    #[case("DEV-FOOBAR", "dev-FOOBAR")]
+    #[case("dev-feature/foo", "dev-feature/foo")]
+    #[case("dev-feature+issue-1", "dev-feature+issue-1")]
+    #[case("dev-master as 1.0.0", "dev-master")]
+    #[case(
+        "dev-load-varnish-only-when-used as ^2.0",
+        "dev-load-varnish-only-when-used"
+    )]
+    #[case(
+        "dev-load-varnish-only-when-used@dev as ^2.0@dev",
+        "dev-load-varnish-only-when-used"
+    )]
+    #[case("1.0.0+foo@dev", "1.0.0.0")]
+    #[case(
+        "dev-load-varnish-only-when-used@stable",
+        "dev-load-varnish-only-when-used"
+    )]
+    #[case("1.0.0-beta.5+foo", "1.0.0.0-beta5")]
+    #[case("1.0.0+foo", "1.0.0.0")]
+    #[case("1.0.0-alpha.3.1+foo", "1.0.0.0-alpha3.1")]
+    #[case("1.0.0-alpha2.1+foo", "1.0.0.0-alpha2.1")]
+    #[case("1.0.0-alpha-2.1-3+foo", "1.0.0.0-alpha2.1-3")]
+    #[case("1.0.0+foo as 2.0", "1.0.0.0")]
+    #[case("8.4.21+heroku1", "8.4.21.0")]
+    #[case("00.01.03.04", "00.01.03.04")]
+    #[case("000.001.003.004", "000.001.003.004")]
+    #[case("0.000.103.204", "0.000.103.204")]
+    #[case("0700", "0700.0.0.0")]
+    #[case("041.x-dev", "041.9999999.9999999.9999999-dev")]
+    #[case("dev-041.003", "dev-041.003")]
+    #[case("dev-1.0.0-dev<1.0.5-dev", "dev-1.0.0-dev<1.0.5-dev")]
+    #[case("dev-foo bar", "dev-foo bar")]
+    #[case(" 1.0.0", "1.0.0.0")]
+    #[case("1.0.0 ", "1.0.0.0")]
+    fn test_normalize(#[case] version: &str, #[case] expected: &str) {
+        assert_eq!(normalize(version).as_deref(), Ok(expected));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("a")]
+    #[case("1.0.0-meh")]
+    #[case("1.0.0.0.0")]
+    #[case("feature-foo")]
+    #[case("1.0.0+foo bar")]
+    #[case("1.0.1-SNAPSHOT")]
+    #[case("1.0.0<1.0.5-dev")]
+    #[case("1.0.0-dev<1.0.5-dev")]
+    #[case("foo bar-dev")]
+    #[case("1.0 .2")]
+    #[case(" as ")]
+    #[case(" as 1.2")]
+    #[case("^")]
+    #[case("~1")]
+    #[case("^1")]
+    #[case("1.*")]
+    #[case("20100102.0.3.4")]
+    #[case("100000.0.0.0")]
+    fn test_normalize_failure(#[case] version: &str) {
+        assert!(normalize(version).is_err());
+    }
+
+    #[rstest]
+    #[case("1.0.0.0", "1.0.0.0", Ordering::Equal)]
+    #[case("1.0.0.0", "1.0.0.0-dev", Ordering::Greater)]
+    #[case("1.0.0.0-alpha1", "1.0.0.0-dev", Ordering::Greater)]
+    #[case("1.0.0.0-beta1", "1.0.0.0-alpha2", Ordering::Greater)]
+    #[case("1.0.0.0-RC1", "1.0.0.0-beta2", Ordering::Greater)]
+    #[case("1.0.0.0", "1.0.0.0-RC1", Ordering::Greater)]
+    #[case("1.0.0.0-patch1", "1.0.0.0", Ordering::Greater)]
+    #[case("1.10.0.0", "1.9.0.0", Ordering::Greater)]
+    #[case("1.0.0", "1.0.0.0", Ordering::Less)]
+    #[case("1.0.9999999.9999999-dev", "1.0.5.0", Ordering::Greater)]
+    fn test_php_version_compare(#[case] a: &str, #[case] b: &str, #[case] expected: Ordering) {
+        assert_eq!(php_version_compare(a, b), expected);
+        assert_eq!(php_version_compare(b, a), expected.reverse());
+    }
+
+    #[rstest]
+    #[case("1.0.0", ComposerStability::Stable)]
+    #[case("1.0.0-patch1", ComposerStability::Stable)]
+    #[case("1.0.0.0-RC1", ComposerStability::Rc)]
+    #[case("1.0.0-beta.2", ComposerStability::Beta)]
+    #[case("1.0.0a1", ComposerStability::Alpha)]
+    #[case("1.0.x-dev", ComposerStability::Dev)]
+    #[case("dev-main", ComposerStability::Dev)]
+    #[case("1.0.0-dev#abcd123", ComposerStability::Dev)]
+    fn test_parse_stability(#[case] version: &str, #[case] expected: ComposerStability) {
+        assert_eq!(parse_stability(version), expected);
+    }
+
+    #[test]
+    fn test_ord() {
+        let mut versions = [
+            "2.x-dev",
+            "dev-master",
+            "1.0.0-patch1",
+            "1.0.0",
+            "v1.0.1",
+            "dev-main",
+            "1.0.0-RC1",
+            "1.0.0-beta2",
+            "dev-feature",
+        ]
+        .map(|v| v.parse::<ComposerVersion>().unwrap());
+        versions.sort();
+        assert_eq!(
+            versions.map(|v| v.to_string()),
+            [
+                "dev-feature",
+                "dev-main",
+                "1.0.0-beta2",
+                "1.0.0-RC1",
+                "1.0.0",
+                "1.0.0-patch1",
+                "v1.0.1",
+                "2.x-dev",
+                "dev-master",
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case("1.2", "v1.2.0.0")]
+    #[case("8.4.21+heroku1", "8.4.21")]
+    #[case("1.0-rc1", "1.0.0.0-RC1")]
+    #[case("master", "dev-master")]
+    fn test_eq(#[case] a: &str, #[case] b: &str) {
+        let a: ComposerVersion = a.parse().unwrap();
+        let b: ComposerVersion = b.parse().unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+    }
+
+    #[rstest]
+    #[case("v8.4.21", Some(8), Some(4), ComposerStability::Stable, false)]
+    #[case("2.6-RC1", Some(2), Some(6), ComposerStability::Rc, false)]
+    #[case("2.x-dev", Some(2), Some(9_999_999), ComposerStability::Dev, false)]
+    #[case(
+        "20100102-203040",
+        Some(20_100_102),
+        None,
+        ComposerStability::Stable,
+        false
+    )]
+    #[case("2010-01-02", Some(2010), None, ComposerStability::Stable, false)]
+    #[case("2010.01.02.03", Some(2010), Some(1), ComposerStability::Stable, false)]
+    #[case("dev-main", None, None, ComposerStability::Dev, true)]
+    fn test_accessors(
+        #[case] version: &str,
+        #[case] major: Option<u64>,
+        #[case] minor: Option<u64>,
+        #[case] stability: ComposerStability,
+        #[case] is_branch: bool,
+    ) {
+        let version: ComposerVersion = version.parse().unwrap();
+        assert_eq!(version.major(), major);
+        assert_eq!(version.minor(), minor);
+        assert_eq!(version.stability(), stability);
+        assert_eq!(version.is_branch(), is_branch);
+    }
+
+    #[test]
+    fn test_serde() {
+        let version: ComposerVersion = serde_json::from_str(r#""v1.2-rc1""#).unwrap();
+        assert_eq!(version.normalized(), "1.2.0.0-RC1");
+        assert_eq!(serde_json::to_string(&version).unwrap(), r#""v1.2-rc1""#);
+        assert!(serde_json::from_str::<ComposerVersion>(r#""1.0.0-meh""#).is_err());
+    }
+}